@@ -0,0 +1,433 @@
+//! Decoding of the accumulator update data served by Hermes, the binary format that carries Pyth
+//! prices from Pythnet to other chains.
+//!
+//! An update starts with the `PNAU` magic and a version, followed by a Wormhole VAA signing the
+//! root of a Merkle tree and the messages (e.g. price feeds) proven against that root. Everything
+//! is decoded in place, no bytes are copied.
+//!
+//! ```
+//! use pyth_min::accumulator::AccumulatorUpdateData;
+//!
+//! # let data = [b'P', b'N', b'A', b'U', 1, 0, 0, 0, 0, 1, 0xaa, 1, 0, 1, 0xbb, 0];
+//! let update = AccumulatorUpdateData::try_from_bytes(&data).unwrap();
+//! for message in update.updates() {
+//!     // Check the message against the root signed in `update.vaa` with `message.verify(&root)`
+//!     # assert_eq!(message.message, &[0xbb]);
+//! }
+//! ```
+
+use crate::{
+    byte_utils::Reader,
+    error::{ParseError, VerifyError},
+    keccak::Keccak256,
+    messages::{Message, PriceFeedMessage},
+};
+
+/// The first bytes of any accumulator update
+pub const ACCUMULATOR_MAGIC: [u8; 4] = *b"PNAU";
+
+/// The only major version of the format. Minor versions are backward compatible.
+pub const MAJOR_VERSION: u8 = 1;
+
+/// Update type of a VAA-signed Merkle root followed by messages with their Merkle proofs
+pub const UPDATE_TYPE_WORMHOLE_MERKLE: u8 = 0;
+
+/// A node of a Merkle proof, the first 20 bytes of a Keccak-256 hash
+pub type MerkleHash = [u8; 20];
+
+/// A borrowed view over accumulator update data.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AccumulatorUpdateData<'a> {
+    pub major_version: u8,
+    pub minor_version: u8,
+    /// Header bytes added by newer minor versions, which this decoder skips
+    pub trailing_header: &'a [u8],
+    /// The Wormhole VAA whose payload holds the Merkle root of the messages
+    pub vaa: &'a [u8],
+    updates: MerkleUpdates<'a>,
+}
+
+impl<'a> AccumulatorUpdateData<'a> {
+    /// Decode and validate the whole update, including the layout of every message and proof, so
+    /// that [`AccumulatorUpdateData::updates`] cannot fail afterwards.
+    ///
+    /// Fails with `InvalidMagic`, `UnsupportedVersion` or `UnknownUpdateType` if `data` is not a
+    /// supported accumulator update, `Truncated` if a length prefix runs past the end of `data`
+    /// and `TrailingBytes` if bytes remain after the last message.
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader::new(data);
+        if *reader.array::<4>()? != ACCUMULATOR_MAGIC {
+            return Err(ParseError::InvalidMagic);
+        }
+        let major_version = reader.u8()?;
+        let minor_version = reader.u8()?;
+        if major_version != MAJOR_VERSION {
+            return Err(ParseError::UnsupportedVersion {
+                major: major_version,
+                minor: minor_version,
+            });
+        }
+        let trailing_header_len = reader.u8()?;
+        let trailing_header = reader.take(trailing_header_len.into())?;
+
+        let update_type = reader.u8()?;
+        if update_type != UPDATE_TYPE_WORMHOLE_MERKLE {
+            return Err(ParseError::UnknownUpdateType(update_type));
+        }
+        let vaa_len = reader.u16()?;
+        let vaa = reader.take(vaa_len.into())?;
+
+        let remaining = reader.u8()?;
+        let updates = MerkleUpdates { reader, remaining };
+        for _ in 0..remaining {
+            read_update(&mut reader)?;
+        }
+        reader.finish()?;
+
+        Ok(AccumulatorUpdateData {
+            major_version,
+            minor_version,
+            trailing_header,
+            vaa,
+            updates,
+        })
+    }
+
+    /// The messages carried by this update, each with its Merkle proof
+    pub fn updates(&self) -> MerkleUpdates<'a> {
+        self.updates
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AccumulatorUpdateData<'a> {
+    type Error = ParseError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(data)
+    }
+}
+
+/// One message of an accumulator update and the proof of its inclusion in the signed Merkle root.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MerklePriceUpdate<'a> {
+    /// The encoded message, e.g. a price feed message
+    pub message: &'a [u8],
+    proof: &'a [MerkleHash],
+}
+
+impl<'a> MerklePriceUpdate<'a> {
+    /// The sibling hashes from the message's leaf up to the root
+    pub fn proof(&self) -> impl ExactSizeIterator<Item = &'a MerkleHash> + 'a {
+        self.proof.iter()
+    }
+
+    /// [`verify_merkle_proof`] of this message against `root`
+    pub fn verify(&self, root: &MerkleHash) -> Result<PriceFeedMessage, VerifyError> {
+        verify_merkle_proof(root, self.message, self.proof())
+    }
+}
+
+fn truncated_keccak(prefix: u8, parts: &[&[u8]]) -> MerkleHash {
+    let mut hasher = Keccak256::new();
+    hasher.update(&[prefix]);
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&hasher.finalize()[..20]);
+    hash
+}
+
+/// The root of the Merkle tree containing `message`, given its proof. Leaves are hashed as
+/// `keccak256(0x00 || message)` and nodes as `keccak256(0x01 || min(a, b) || max(a, b))`, both
+/// truncated to 20 bytes.
+pub fn merkle_root<'a>(
+    message: &[u8],
+    proof: impl IntoIterator<Item = &'a MerkleHash>,
+) -> MerkleHash {
+    let mut hash = truncated_keccak(0, &[message]);
+    for sibling in proof {
+        let (low, high) = if hash <= *sibling {
+            (&hash, sibling)
+        } else {
+            (sibling, &hash)
+        };
+        hash = truncated_keccak(1, &[low, high]);
+    }
+    hash
+}
+
+/// Check that the wire encoded `message` is committed to by `root`, the Merkle root signed by the
+/// accumulator VAA, and decode it.
+///
+/// Fails with `InvalidMerkleProof` if the proof does not lead to `root`, and with
+/// `NotAPriceFeedMessage` if the message is authentic but of another type.
+pub fn verify_merkle_proof<'a>(
+    root: &MerkleHash,
+    message: &[u8],
+    proof: impl IntoIterator<Item = &'a MerkleHash>,
+) -> Result<PriceFeedMessage, VerifyError> {
+    if merkle_root(message, proof) != *root {
+        return Err(VerifyError::InvalidMerkleProof);
+    }
+    match Message::decode_wire(message)? {
+        Message::PriceFeed(price_feed) => Ok(price_feed),
+        _ => Err(VerifyError::NotAPriceFeedMessage),
+    }
+}
+
+fn read_update<'a>(reader: &mut Reader<'a>) -> Result<MerklePriceUpdate<'a>, ParseError> {
+    let message_len = reader.u16()?;
+    let message = reader.take(message_len.into())?;
+    let proof_len = reader.u8()?;
+    let proof = reader.take(usize::from(proof_len) * core::mem::size_of::<MerkleHash>())?;
+    // `take` returned whole hashes, so there is no remainder
+    let (proof, _) = proof.as_chunks();
+    Ok(MerklePriceUpdate { message, proof })
+}
+
+/// Iterator over the messages of an [`AccumulatorUpdateData`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MerkleUpdates<'a> {
+    reader: Reader<'a>,
+    remaining: u8,
+}
+
+impl<'a> Iterator for MerkleUpdates<'a> {
+    type Item = MerklePriceUpdate<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // Already validated when the update was decoded
+        read_update(&mut self.reader).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining.into(), Some(self.remaining.into()))
+    }
+}
+
+impl ExactSizeIterator for MerkleUpdates<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_utils::hex_to_bytes;
+
+    use super::*;
+
+    fn encode(header: &[u8], vaa: &[u8], updates: &[(&[u8], &[MerkleHash])]) -> Vec<u8> {
+        let mut data = b"PNAU".to_vec();
+        data.extend_from_slice(header);
+        data.push(UPDATE_TYPE_WORMHOLE_MERKLE);
+        data.extend_from_slice(&(vaa.len() as u16).to_be_bytes());
+        data.extend_from_slice(vaa);
+        data.push(updates.len() as u8);
+        for (message, proof) in updates {
+            data.extend_from_slice(&(message.len() as u16).to_be_bytes());
+            data.extend_from_slice(message);
+            data.push(proof.len() as u8);
+            for node in *proof {
+                data.extend_from_slice(node);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn decode_update() {
+        let vaa = [7u8; 300];
+        let message_a = [1u8; 85];
+        let message_b = [2u8; 3];
+        let data = encode(
+            &[1, 0, 0],
+            &vaa,
+            &[(&message_a, &[[0xa1; 20], [0xa2; 20]]), (&message_b, &[])],
+        );
+
+        let update = AccumulatorUpdateData::try_from_bytes(&data).unwrap();
+        assert_eq!((update.major_version, update.minor_version), (1, 0));
+        assert!(update.trailing_header.is_empty());
+        assert_eq!(update.vaa, &vaa);
+
+        let mut updates = update.updates();
+        assert_eq!(updates.len(), 2);
+        let first = updates.next().unwrap();
+        assert_eq!(first.message, &message_a);
+        assert!(first.proof().eq([&[0xa1; 20], &[0xa2; 20]]));
+        let second = updates.next().unwrap();
+        assert_eq!(second.message, &message_b);
+        assert_eq!(second.proof().len(), 0);
+        assert_eq!(updates.next(), None);
+
+        // Iterating again starts over
+        assert_eq!(update.updates().count(), 2);
+    }
+
+    #[test]
+    fn decode_newer_minor_version() {
+        let data = encode(&[1, 3, 2, 0xde, 0xad], &[9], &[]);
+
+        let update = AccumulatorUpdateData::try_from(&data[..]).unwrap();
+        assert_eq!(update.minor_version, 3);
+        assert_eq!(update.trailing_header, &[0xde, 0xad]);
+        assert_eq!(update.updates().next(), None);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let data = encode(&[1, 0, 0], &[9; 4], &[(&[5; 10], &[[6; 20]])]);
+        assert!(AccumulatorUpdateData::try_from_bytes(&data).is_ok());
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'Q';
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&bad_magic),
+            Err(ParseError::InvalidMagic)
+        );
+
+        let mut bad_version = data.clone();
+        bad_version[4] = 2;
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&bad_version),
+            Err(ParseError::UnsupportedVersion { major: 2, minor: 0 })
+        );
+
+        let mut bad_type = data.clone();
+        bad_type[7] = 1;
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&bad_type),
+            Err(ParseError::UnknownUpdateType(1))
+        );
+
+        // Cut in the middle of the proof
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&data[..data.len() - 1]),
+            Err(ParseError::Truncated {
+                expected: data.len(),
+                actual: data.len() - 1
+            })
+        );
+        // A VAA length running past the end
+        let mut long_vaa = data.clone();
+        long_vaa[8] = 0xff;
+        assert!(matches!(
+            AccumulatorUpdateData::try_from_bytes(&long_vaa),
+            Err(ParseError::Truncated { .. })
+        ));
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&trailing),
+            Err(ParseError::TrailingBytes {
+                expected: data.len(),
+                actual: data.len() + 1
+            })
+        );
+        assert_eq!(
+            AccumulatorUpdateData::try_from_bytes(&[]),
+            Err(ParseError::Truncated {
+                expected: 4,
+                actual: 0
+            })
+        );
+    }
+
+    fn price_feed_wire(price: i64) -> [u8; 85] {
+        let mut wire = [0u8; 85];
+        Message::PriceFeed(PriceFeedMessage {
+            feed_id: [1u8; 32],
+            price,
+            conf: 10,
+            exponent: -8,
+            publish_time: 100,
+            prev_publish_time: 99,
+            ema_price: price,
+            ema_conf: 10,
+        })
+        .encode_wire(&mut wire)
+        .unwrap();
+        wire
+    }
+
+    #[test]
+    fn merkle_hashing() {
+        // keccak256(0x00) and keccak256(0x00 || 0x00)
+        assert_eq!(
+            merkle_root(&[], []).to_vec(),
+            hex_to_bytes("bc36789e7a1e281436464229828f817d6612f7b4")
+        );
+        assert_eq!(
+            merkle_root(&[0], []).to_vec(),
+            hex_to_bytes("54a8c0ab653c15bfb48b47fd011ba2b9617af01c")
+        );
+
+        // The order of the siblings in a node does not depend on the side of the leaf
+        let a = merkle_root(b"a", []);
+        let b = merkle_root(b"b", []);
+        assert_eq!(merkle_root(b"a", [&b]), merkle_root(b"b", [&a]));
+    }
+
+    fn merkle_root_of_nodes(a: &MerkleHash, b: &MerkleHash) -> MerkleHash {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        truncated_keccak(1, &[low, high])
+    }
+
+    #[test]
+    fn verify_proofs_of_a_four_leaf_tree() {
+        let messages = [
+            price_feed_wire(1),
+            price_feed_wire(2),
+            price_feed_wire(3),
+            price_feed_wire(4),
+        ];
+        let leaves = messages.map(|message| merkle_root(&message, []));
+        let left = merkle_root_of_nodes(&leaves[0], &leaves[1]);
+        let right = merkle_root_of_nodes(&leaves[2], &leaves[3]);
+        let root = merkle_root_of_nodes(&left, &right);
+
+        for (i, message) in messages.iter().enumerate() {
+            let proof = [leaves[i ^ 1], if i < 2 { right } else { left }];
+            let verified = verify_merkle_proof(&root, message, &proof).unwrap();
+            assert_eq!(verified.price, i as i64 + 1);
+        }
+
+        // Wrong sibling, wrong root, tampered message
+        assert_eq!(
+            verify_merkle_proof(&root, &messages[0], &[leaves[2], right]),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+        assert_eq!(
+            verify_merkle_proof(&left, &messages[0], &[leaves[1], right]),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+        let mut tampered = messages[0];
+        tampered[40] ^= 1;
+        assert_eq!(
+            verify_merkle_proof(&root, &tampered, &[leaves[1], right]),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+    }
+
+    #[test]
+    fn verify_update_from_accumulator() {
+        let message = price_feed_wire(42);
+        let sibling = [9u8; 20];
+        let data = encode(&[1, 0, 0], &[], &[(&message, &[sibling]), (&[7], &[])]);
+        let root = merkle_root(&message, [&sibling]);
+
+        let update = AccumulatorUpdateData::try_from_bytes(&data).unwrap();
+        let mut updates = update.updates();
+        assert_eq!(updates.next().unwrap().verify(&root).unwrap().price, 42);
+
+        // Authentic but not decodable
+        let unknown = updates.next().unwrap();
+        assert_eq!(
+            unknown.verify(&merkle_root(&[7], [])),
+            Err(VerifyError::Parse(ParseError::UnknownMessageType(7)))
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use crate::{
+    error::{GetPriceError, Result},
+    math::{div_round, Rounding},
+    policy::PriceValidationPolicy,
+    price_update::Price,
+    source::PriceSource,
+};
+
+/// The most price sources [`aggregate`] accepts in one call.
+pub const MAX_SOURCES: usize = 32;
+
+/// How the prices of several sources for the same asset are combined.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AggregationMethod {
+    /// The median price. With an even number of sources, the mean of the two middle prices.
+    Median,
+    /// The price with the most recent publish time, ties going to the narrowest confidence.
+    Freshest,
+    /// The mean of the prices weighted by `1 / conf`, so precise sources count more. Sources with
+    /// a zero confidence, if any, are the only ones that count.
+    ConfidenceWeightedMean,
+}
+
+/// The result of [`aggregate`], along with which sources were used.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AggregatedPrice {
+    /// The combined price, see [`aggregate`] for how its confidence and publish time are derived
+    pub price: Price,
+    /// Bit `i` is set if source `i` passed validation and was aggregated
+    pub used_mask: u32,
+}
+
+impl AggregatedPrice {
+    /// Whether source `index` was used
+    pub fn is_used(&self, index: usize) -> bool {
+        index < MAX_SOURCES && self.used_mask & (1 << index) != 0
+    }
+
+    /// How many sources were used
+    pub fn num_used(&self) -> u32 {
+        self.used_mask.count_ones()
+    }
+}
+
+/// Combine several candidate prices for the same asset, skipping the candidates that are errors.
+/// Candidates are typically the results of [`PriceValidationPolicy::validate`] on several
+/// accounts, or prices read from other oracles.
+///
+/// Every price is first expressed with `result_exponent`, and candidates that cannot be are skipped
+/// too. Fails with `NotEnoughPriceSources` if fewer than `min_sources` candidates remain, and with
+/// `TooManyPriceSources` if more than [`MAX_SOURCES`] are given.
+///
+/// The confidence of the result covers the disagreement between sources: each source contributes
+/// `conf + |price - aggregated price|`, and these are combined the same way as the prices (the
+/// upper median for [`AggregationMethod::Median`], the weighted mean for
+/// [`AggregationMethod::ConfidenceWeightedMean`]). [`AggregationMethod::Freshest`] keeps the
+/// confidence of the chosen source. The publish time is that of the stalest source used, or of the
+/// chosen source for `Freshest`.
+///
+/// ```
+/// use pyth_min::{
+///     aggregate::{aggregate, AggregationMethod},
+///     error::GetPriceError,
+///     price_update::Price,
+/// };
+///
+/// let candidates = [
+///     Ok(Price { price: 10100, conf: 10, exponent: -2, publish_time: 100 }),
+///     Err(GetPriceError::PriceTooOld),
+///     Ok(Price { price: 1000, conf: 1, exponent: -1, publish_time: 98 }),
+///     Ok(Price { price: 9950, conf: 20, exponent: -2, publish_time: 99 }),
+/// ];
+///
+/// let median = aggregate(&candidates, AggregationMethod::Median, 2, -2).unwrap();
+/// assert_eq!((median.price.price, median.price.conf), (10000, 70));
+/// assert_eq!(median.used_mask, 0b1101);
+/// ```
+pub fn aggregate(
+    candidates: &[Result<Price>],
+    method: AggregationMethod,
+    min_sources: usize,
+    result_exponent: i32,
+) -> Result<AggregatedPrice> {
+    aggregate_iter(
+        candidates
+            .iter()
+            .map(|candidate| candidate.as_ref().ok().copied()),
+        method,
+        min_sources,
+        result_exponent,
+    )
+}
+
+/// Validate every source against `policy` at unix time `now`, then [`aggregate`] the ones that
+/// pass.
+pub fn aggregate_updates<S: PriceSource>(
+    updates: &[S],
+    policy: &PriceValidationPolicy,
+    now: i64,
+    method: AggregationMethod,
+    min_sources: usize,
+    result_exponent: i32,
+) -> Result<AggregatedPrice> {
+    aggregate_iter(
+        updates
+            .iter()
+            .map(|update| policy.validate(update, now).ok()),
+        method,
+        min_sources,
+        result_exponent,
+    )
+}
+
+fn aggregate_iter(
+    candidates: impl Iterator<Item = Option<Price>>,
+    method: AggregationMethod,
+    min_sources: usize,
+    result_exponent: i32,
+) -> Result<AggregatedPrice> {
+    let mut prices = [Price {
+        price: 0,
+        conf: 0,
+        exponent: result_exponent,
+        publish_time: 0,
+    }; MAX_SOURCES];
+    let mut len = 0;
+    let mut used_mask = 0u32;
+    for (i, candidate) in candidates.enumerate() {
+        if i == MAX_SOURCES {
+            return Err(GetPriceError::TooManyPriceSources);
+        }
+        let Some(price) = candidate else { continue };
+        let Ok(price) = price.scale_to_exponent(result_exponent, Rounding::HalfEven) else {
+            continue;
+        };
+        prices[len] = price;
+        len += 1;
+        used_mask |= 1 << i;
+    }
+    if len == 0 || len < min_sources {
+        return Err(GetPriceError::NotEnoughPriceSources {
+            used: used_mask.count_ones(),
+        });
+    }
+    let prices = &mut prices[..len];
+
+    let price = match method {
+        AggregationMethod::Median => median(prices)?,
+        AggregationMethod::Freshest => freshest(prices),
+        AggregationMethod::ConfidenceWeightedMean => weighted_mean(prices)?,
+    };
+    Ok(AggregatedPrice { price, used_mask })
+}
+
+/// `conf + |price - center|` of one source
+fn spread(price: &Price, center: i128) -> i128 {
+    i128::from(price.conf) + (i128::from(price.price) - center).abs()
+}
+
+fn stalest(prices: &[Price]) -> i64 {
+    prices.iter().map(|p| p.publish_time).min().unwrap_or(0)
+}
+
+fn median(prices: &mut [Price]) -> Result<Price> {
+    prices.sort_unstable_by_key(|p| p.price);
+    let mid = prices.len() / 2;
+    let center = if prices.len() % 2 == 1 {
+        i128::from(prices[mid].price)
+    } else {
+        div_round(
+            i128::from(prices[mid - 1].price) + i128::from(prices[mid].price),
+            2,
+            Rounding::HalfEven,
+        )
+    };
+
+    let mut spreads = [0i128; MAX_SOURCES];
+    for (spread_i, price) in spreads.iter_mut().zip(prices.iter()) {
+        *spread_i = spread(price, center);
+    }
+    let spreads = &mut spreads[..prices.len()];
+    spreads.sort_unstable();
+
+    Ok(Price {
+        price: i64::try_from(center).map_err(|_| GetPriceError::MathOverflow)?,
+        conf: u64::try_from(spreads[mid]).map_err(|_| GetPriceError::MathOverflow)?,
+        exponent: prices[0].exponent,
+        publish_time: stalest(prices),
+    })
+}
+
+fn freshest(prices: &[Price]) -> Price {
+    let mut best = prices[0];
+    for price in &prices[1..] {
+        if (price.publish_time, core::cmp::Reverse(price.conf))
+            > (best.publish_time, core::cmp::Reverse(best.conf))
+        {
+            best = *price;
+        }
+    }
+    best
+}
+
+fn weighted_mean(prices: &[Price]) -> Result<Price> {
+    // Weights are relative to the narrowest confidence, which gets 2^32, so the sums below stay
+    // well within an i128
+    let min_conf = prices.iter().map(|p| p.conf).min().unwrap_or(0);
+    let weight = |price: &Price| -> i128 {
+        if min_conf == 0 {
+            i128::from(price.conf == 0)
+        } else {
+            (i128::from(min_conf) << 32) / i128::from(price.conf)
+        }
+    };
+
+    let total_weight: i128 = prices.iter().map(weight).sum();
+    let weighted_price: i128 = prices.iter().map(|p| weight(p) * i128::from(p.price)).sum();
+    let center = div_round(weighted_price, total_weight, Rounding::HalfEven);
+    let weighted_spread: i128 = prices.iter().map(|p| weight(p) * spread(p, center)).sum();
+    let conf = div_round(weighted_spread, total_weight, Rounding::Ceil);
+
+    Ok(Price {
+        price: i64::try_from(center).map_err(|_| GetPriceError::MathOverflow)?,
+        conf: u64::try_from(conf).map_err(|_| GetPriceError::MathOverflow)?,
+        exponent: prices[0].exponent,
+        publish_time: stalest(prices),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{price, MockPriceUpdate};
+
+    use super::*;
+
+    #[test]
+    fn median_odd_and_even() {
+        let candidates = [
+            Ok(price(100, 1, -2, 10)),
+            Ok(price(300, 1, -2, 12)),
+            Ok(price(200, 2, -2, 11)),
+        ];
+        let result = aggregate(&candidates, AggregationMethod::Median, 1, -2).unwrap();
+        // Spreads are 101, 2 and 101
+        assert_eq!(result.price, price(200, 101, -2, 10));
+        assert_eq!(result.num_used(), 3);
+
+        let candidates = [Ok(price(100, 1, -2, 10)), Ok(price(201, 1, -2, 12))];
+        let result = aggregate(&candidates, AggregationMethod::Median, 1, -2).unwrap();
+        // 150.5 rounds to the even 150, spreads are 51 and 52
+        assert_eq!(result.price, price(150, 52, -2, 10));
+    }
+
+    #[test]
+    fn median_ignores_outlier() {
+        let candidates = [
+            Ok(price(100, 1, -2, 10)),
+            Ok(price(101, 1, -2, 10)),
+            Ok(price(99, 1, -2, 10)),
+            Ok(price(100, 2, -2, 10)),
+            Ok(price(1_000_000, 1, -2, 10)),
+        ];
+        let result = aggregate(&candidates, AggregationMethod::Median, 3, -2).unwrap();
+        assert_eq!(result.price, price(100, 2, -2, 10));
+    }
+
+    #[test]
+    fn freshest() {
+        let candidates = [
+            Ok(price(100, 5, -2, 10)),
+            Ok(price(110, 9, -2, 12)),
+            Err(GetPriceError::PriceTooOld),
+            Ok(price(120, 3, -2, 12)),
+        ];
+        let result = aggregate(&candidates, AggregationMethod::Freshest, 1, -2).unwrap();
+        assert_eq!(result.price, price(120, 3, -2, 12));
+        assert_eq!(result.used_mask, 0b1011);
+        assert!(!result.is_used(2));
+    }
+
+    #[test]
+    fn confidence_weighted_mean() {
+        // Weights 2 : 1
+        let candidates = [Ok(price(100, 1, -2, 10)), Ok(price(130, 2, -2, 11))];
+        let result = aggregate(
+            &candidates,
+            AggregationMethod::ConfidenceWeightedMean,
+            1,
+            -2,
+        )
+        .unwrap();
+        // (2 * 100 + 130) / 3 = 110, conf (2 * 11 + 22) / 3 rounded up
+        assert_eq!(result.price, price(110, 15, -2, 10));
+
+        // Exact prices take over
+        let candidates = [Ok(price(100, 0, -2, 10)), Ok(price(130, 2, -2, 11))];
+        let result = aggregate(
+            &candidates,
+            AggregationMethod::ConfidenceWeightedMean,
+            1,
+            -2,
+        )
+        .unwrap();
+        assert_eq!(result.price, price(100, 0, -2, 10));
+
+        let extreme = [
+            Ok(price(i64::MAX, 1, -2, 0)),
+            Ok(price(i64::MAX, u64::MAX, -2, 0)),
+        ];
+        assert!(aggregate(&extreme, AggregationMethod::ConfidenceWeightedMean, 1, -2).is_ok());
+    }
+
+    #[test]
+    fn not_enough_or_too_many_sources() {
+        let candidates = [
+            Ok(price(100, 1, -2, 10)),
+            Err(GetPriceError::PriceTooOld),
+            // Does not fit an i64 at the result exponent
+            Ok(Price {
+                exponent: 0,
+                ..price(i64::MAX, 0, -2, 10)
+            }),
+        ];
+        assert_eq!(
+            aggregate(&candidates, AggregationMethod::Median, 2, -2),
+            Err(GetPriceError::NotEnoughPriceSources { used: 1 })
+        );
+        assert_eq!(
+            aggregate(&candidates[1..2], AggregationMethod::Median, 0, -2),
+            Err(GetPriceError::NotEnoughPriceSources { used: 0 })
+        );
+
+        let candidates: [Result<Price>; MAX_SOURCES + 1] =
+            core::array::from_fn(|_| Ok(price(100, 1, -2, 10)));
+        assert_eq!(
+            aggregate(&candidates, AggregationMethod::Median, 1, -2),
+            Err(GetPriceError::TooManyPriceSources)
+        );
+        assert!(aggregate(&candidates[1..], AggregationMethod::Median, 1, -2).is_ok());
+    }
+
+    #[test]
+    fn aggregate_updates_skips_stale() {
+        let now = 1_700_000_000;
+        let policy = PriceValidationPolicy::new(30);
+        let updates = [
+            MockPriceUpdate::new().age_seconds(now, 5).build(),
+            MockPriceUpdate::new()
+                .price(200_00000000)
+                .age_seconds(now, 60)
+                .build(),
+            MockPriceUpdate::new()
+                .price(102_00000000)
+                .age_seconds(now, 10)
+                .build(),
+        ];
+
+        let result =
+            aggregate_updates(&updates, &policy, now, AggregationMethod::Median, 2, -8).unwrap();
+        assert_eq!(result.price.price, 101_00000000);
+        assert_eq!(result.price.publish_time, now - 10);
+        assert_eq!(result.used_mask, 0b101);
+    }
+}
@@ -1,12 +1,17 @@
+use crate::error::ParseError;
+
 /// The discriminator of Price Feed Accounts on mainnet
 pub const DISCRIMINATOR_AS_HEX: &str = "22f123639d7ef4cd";
-pub const DISCRIMINATOR_AS_BYTES: &[i32; 8] = &[0x22, 0xF1, 0x23, 0x63, 0x9D, 0x7E, 0xF4, 0xCD];
+pub const DISCRIMINATOR_AS_BYTES: [u8; 8] = [0x22, 0xF1, 0x23, 0x63, 0x9D, 0x7E, 0xF4, 0xCD];
 
 /// Bytes that will be a Pubkey when decoded (this crate has dependencies and therefore does not
 /// read Pubkeys)
 pub type PubkeyBytes = [u8; 32];
 
 /// A very minimal tool to convert a hex string like "22f123639" into the byte equivalent.
+///
+/// Requires the `std` feature, as it allocates.
+#[cfg(any(feature = "std", test))]
 pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
     hex.as_bytes()
         .chunks(2)
@@ -20,6 +25,16 @@ pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
         .collect()
 }
 
+/// Decode a single ASCII hex digit (either case) into its value, or `None` if it is not hex.
+pub const fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
 /// A very minimal utility to interpret some bytes as an i64
 pub fn interpret_bytes_as_i64(bytes: &[u8]) -> i64 {
     let mut arr = [0u8; 8];
@@ -39,4 +54,122 @@ pub fn interpret_bytes_as_u64(bytes: &[u8]) -> u64 {
     let mut arr = [0u8; 8];
     arr.copy_from_slice(bytes);
     u64::from_le_bytes(arr)
-}
\ No newline at end of file
+}
+
+/// A cursor over big-endian wire data (Wormhole VAAs and Pythnet accumulator messages), reporting
+/// offsets relative to the start of the input in its errors.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ParseError::Truncated {
+                expected: self.position.saturating_add(len),
+                actual: self.bytes.len(),
+            })?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<&'a [u8; N], ParseError> {
+        let taken = self.take(N)?;
+        // Infallible, `take` returned exactly N bytes
+        taken.try_into().map_err(|_| ParseError::Truncated {
+            expected: N,
+            actual: taken.len(),
+        })
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, ParseError> {
+        Ok(u16::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, ParseError> {
+        Ok(u32::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, ParseError> {
+        Ok(i32::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, ParseError> {
+        Ok(u64::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn i64(&mut self) -> Result<i64, ParseError> {
+        Ok(i64::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn u128(&mut self) -> Result<u128, ParseError> {
+        Ok(u128::from_be_bytes(*self.array()?))
+    }
+
+    pub(crate) fn i128(&mut self) -> Result<i128, ParseError> {
+        Ok(i128::from_be_bytes(*self.array()?))
+    }
+
+    /// The bytes not read yet
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Fails with `TrailingBytes` unless the whole input was read
+    pub(crate) fn finish(&self) -> Result<(), ParseError> {
+        if self.position != self.bytes.len() {
+            return Err(ParseError::TrailingBytes {
+                expected: self.position,
+                actual: self.bytes.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The writing counterpart of [`Reader`].
+#[derive(Debug)]
+pub(crate) struct Writer<'a> {
+    bytes: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub(crate) fn new(bytes: &'a mut [u8]) -> Self {
+        Writer { bytes, position: 0 }
+    }
+
+    /// Fails with `Truncated` if `data` does not fit in the remaining space
+    pub(crate) fn put(&mut self, data: &[u8]) -> Result<(), ParseError> {
+        let end = self
+            .position
+            .checked_add(data.len())
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ParseError::Truncated {
+                expected: self.position.saturating_add(data.len()),
+                actual: self.bytes.len(),
+            })?;
+        self.bytes[self.position..end].copy_from_slice(data);
+        self.position = end;
+        Ok(())
+    }
+
+    /// How many bytes were written
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+}
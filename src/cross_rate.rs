@@ -0,0 +1,289 @@
+use crate::{
+    error::{GetPriceError, Result},
+    math::{mul_pow10_div, Rounding},
+    price_update::Price,
+};
+
+/// Confidence of a derived price, as the sum of two terms `n * 10^exp / d`, each rounded up.
+fn sum_conf_terms(terms: [(i128, i128); 2], exp: i64) -> Result<u64> {
+    let mut conf: u64 = 0;
+    for (n, d) in terms {
+        let term = mul_pow10_div(n, exp, d, Rounding::Ceil)?;
+        let term = u64::try_from(term).map_err(|_| GetPriceError::MathOverflow)?;
+        conf = conf.checked_add(term).ok_or(GetPriceError::MathOverflow)?;
+    }
+    Ok(conf)
+}
+
+impl Price {
+    /// Derive the price of this asset in terms of `quote`, when both are quoted in the same
+    /// currency. For example the JITO/SOL price is `jito_usd.get_price_in_quote(&sol_usd, ..)`.
+    ///
+    /// The result is expressed with `result_exponent` and its price rounded with `rounding`. The
+    /// relative confidences of both inputs add up, i.e. `conf / |price|` of the result is
+    /// `conf_a / |price_a| + conf_b / |price_b|`, rounded up. `publish_time` is the older of the
+    /// two, as the derived price is only as fresh as its stalest input.
+    ///
+    /// Fails with `ZeroPrice` or `NegativePrice` if `quote` cannot be divided by.
+    ///
+    /// ```
+    /// use pyth_min::{math::Rounding, price_update::Price};
+    ///
+    /// let jito_usd = Price { price: 300_000_000, conf: 3_000_000, exponent: -8, publish_time: 10 };
+    /// let sol_usd = Price { price: 150_00000000, conf: 0, exponent: -8, publish_time: 12 };
+    ///
+    /// let jito_sol = jito_usd.get_price_in_quote(&sol_usd, -9, Rounding::HalfEven).unwrap();
+    /// assert_eq!((jito_sol.price, jito_sol.conf), (20_000_000, 200_000));
+    /// assert_eq!(jito_sol.publish_time, 10);
+    /// ```
+    pub fn get_price_in_quote(
+        &self,
+        quote: &Price,
+        result_exponent: i32,
+        rounding: Rounding,
+    ) -> Result<Price> {
+        if quote.price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+        if quote.price == 0 {
+            return Err(GetPriceError::ZeroPrice);
+        }
+
+        // (a * 10^ea) / (b * 10^eb) = (a / b) * 10^(ea - eb)
+        let exp = i64::from(self.exponent) - i64::from(quote.exponent) - i64::from(result_exponent);
+        let a = i128::from(self.price);
+        let b = i128::from(quote.price);
+        let price = mul_pow10_div(a, exp, b, rounding)?;
+        // conf_a / |b| + conf_b * |a| / b^2
+        let conf = sum_conf_terms(
+            [
+                (self.conf.into(), b),
+                (i128::from(quote.conf) * a.abs(), b * b),
+            ],
+            exp,
+        )?;
+
+        Ok(Price {
+            price: i64::try_from(price).map_err(|_| GetPriceError::MathOverflow)?,
+            conf,
+            exponent: result_exponent,
+            publish_time: self.publish_time.min(quote.publish_time),
+        })
+    }
+
+    /// Multiply two prices, e.g. derive JITO/USD from JITO/SOL and SOL/USD.
+    ///
+    /// Like [`Price::get_price_in_quote`], the result is expressed with `result_exponent`, the
+    /// relative confidences add up and `publish_time` is the older of the two.
+    pub fn mul_price(
+        &self,
+        other: &Price,
+        result_exponent: i32,
+        rounding: Rounding,
+    ) -> Result<Price> {
+        // (a * 10^ea) * (b * 10^eb) = (a * b) * 10^(ea + eb)
+        let exp = i64::from(self.exponent) + i64::from(other.exponent) - i64::from(result_exponent);
+        let a = i128::from(self.price);
+        let b = i128::from(other.price);
+        let price = mul_pow10_div(a * b, exp, 1, rounding)?;
+        // conf_a * |b| + conf_b * |a|
+        let conf = sum_conf_terms(
+            [
+                (i128::from(self.conf) * b.abs(), 1),
+                (i128::from(other.conf) * a.abs(), 1),
+            ],
+            exp,
+        )?;
+
+        Ok(Price {
+            price: i64::try_from(price).map_err(|_| GetPriceError::MathOverflow)?,
+            conf,
+            exponent: result_exponent,
+            publish_time: self.publish_time.min(other.publish_time),
+        })
+    }
+
+    /// The reciprocal price, e.g. USD/SOL from SOL/USD, expressed with `result_exponent` and
+    /// rounded with `rounding`.
+    ///
+    /// The interval `[price - conf, price + conf]` maps to `[1 / (price + conf), 1 / (price - conf)]`,
+    /// which is not symmetric around `1 / price`. The returned `conf` is the larger side,
+    /// `conf / (price * (price - conf))`, rounded up, so the result still covers the whole interval.
+    ///
+    /// Fails with `ZeroPrice` or `NegativePrice` if the price cannot be inverted, and with
+    /// `ConfidenceTooWide` if the confidence interval contains zero.
+    ///
+    /// ```
+    /// use pyth_min::{math::Rounding, price_update::Price};
+    ///
+    /// // SOL/USD 125 ± 25, so USD/SOL lies within [1 / 150, 1 / 100]
+    /// let sol_usd = Price { price: 125, conf: 25, exponent: 0, publish_time: 0 };
+    ///
+    /// let usd_sol = sol_usd.invert(-4, Rounding::HalfEven).unwrap();
+    /// assert_eq!((usd_sol.price, usd_sol.conf, usd_sol.exponent), (80, 20, -4));
+    /// ```
+    pub fn invert(&self, result_exponent: i32, rounding: Rounding) -> Result<Price> {
+        if self.price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+        if self.price == 0 {
+            return Err(GetPriceError::ZeroPrice);
+        }
+        if u128::from(self.conf) >= self.price.unsigned_abs().into() {
+            return Err(GetPriceError::ConfidenceTooWide {
+                conf_ratio_bps: self.conf_ratio_bps(),
+            });
+        }
+
+        // 1 / (a * 10^e) = (1 / a) * 10^-e
+        let exp = -i64::from(self.exponent) - i64::from(result_exponent);
+        let a = i128::from(self.price);
+        let c = i128::from(self.conf);
+        let price = mul_pow10_div(1, exp, a, rounding)?;
+        // 1 / (a - c) - 1 / a = c / (a * (a - c))
+        let conf = mul_pow10_div(c, exp, a * (a - c), Rounding::Ceil)?;
+
+        Ok(Price {
+            price: i64::try_from(price).map_err(|_| GetPriceError::MathOverflow)?,
+            conf: u64::try_from(conf).map_err(|_| GetPriceError::MathOverflow)?,
+            exponent: result_exponent,
+            publish_time: self.publish_time,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::price;
+
+    use super::*;
+
+    #[test]
+    fn price_in_quote() {
+        // JITO/USD 3.00 ± 0.03 (1%), SOL/USD 150.00 ± 3.00 (2%)
+        let jito_usd = price(300_000_000, 3_000_000, -8, 100);
+        let sol_usd = price(150_00000000, 3_00000000, -8, 90);
+
+        let jito_sol = jito_usd
+            .get_price_in_quote(&sol_usd, -9, Rounding::HalfEven)
+            .unwrap();
+        // 0.02 SOL ± 3%
+        assert_eq!(jito_sol, price(20_000_000, 600_000, -9, 90));
+
+        // Different input exponents
+        let sol_usd = price(150_000, 3_000, -3, 90);
+        assert_eq!(
+            jito_usd.get_price_in_quote(&sol_usd, -9, Rounding::HalfEven),
+            Ok(jito_sol)
+        );
+    }
+
+    #[test]
+    fn price_in_quote_rounding() {
+        // 1 / 3
+        let one = price(1, 0, 0, 0);
+        let three = price(3, 1, 0, 0);
+
+        let floor = one.get_price_in_quote(&three, -4, Rounding::Floor).unwrap();
+        assert_eq!(floor.price, 3333);
+        // conf is 1/9 rounded up
+        assert_eq!(floor.conf, 1112);
+        let ceil = one.get_price_in_quote(&three, -4, Rounding::Ceil).unwrap();
+        assert_eq!(ceil.price, 3334);
+    }
+
+    #[test]
+    fn price_in_quote_errors() {
+        let one = price(1, 0, 0, 0);
+        assert_eq!(
+            one.get_price_in_quote(&price(0, 0, 0, 0), 0, Rounding::Floor),
+            Err(GetPriceError::ZeroPrice)
+        );
+        assert_eq!(
+            one.get_price_in_quote(&price(-1, 0, 0, 0), 0, Rounding::Floor),
+            Err(GetPriceError::NegativePrice)
+        );
+        assert_eq!(
+            price(i64::MAX, 0, 0, 0).get_price_in_quote(&one, -1, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+        assert_eq!(
+            price(i64::MAX, u64::MAX, 0, 0).get_price_in_quote(
+                &price(i64::MAX, u64::MAX, 0, 0),
+                0,
+                Rounding::Floor
+            ),
+            Ok(price(1, 6, 0, 0))
+        );
+    }
+
+    #[test]
+    fn mul_price() {
+        // JITO/SOL 0.02 ± 1%, SOL/USD 150.00 ± 2%
+        let jito_sol = price(20_000_000, 200_000, -9, 100);
+        let sol_usd = price(150_00000000, 3_00000000, -8, 90);
+
+        let jito_usd = jito_sol
+            .mul_price(&sol_usd, -8, Rounding::HalfEven)
+            .unwrap();
+        // 3.00 ± 3%
+        assert_eq!(jito_usd, price(300_000_000, 9_000_000, -8, 90));
+
+        assert_eq!(
+            price(i64::MAX, 0, 0, 0).mul_price(&price(2, 0, 0, 0), 0, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn invert() {
+        // SOL/USD 167.06469648 ± 0.22390601
+        let sol_usd = price(16706469648, 22390601, -8, 7);
+
+        // USD/SOL 0.00598570506558 ± 0.00000803301959
+        let usd_sol = sol_usd.invert(-12, Rounding::HalfEven).unwrap();
+        assert_eq!(usd_sol, price(5_985_705_066, 8_033_020, -12, 7));
+        assert_eq!(
+            sol_usd.invert(-12, Rounding::Floor).unwrap().price,
+            5_985_705_065
+        );
+        assert_eq!(
+            sol_usd.invert(-12, Rounding::Ceil).unwrap().price,
+            5_985_705_066
+        );
+
+        // A price without confidence inverts like a division
+        let exact = price(16706469648, 0, -8, 7);
+        assert_eq!(
+            exact.invert(-12, Rounding::HalfEven),
+            price(1, 0, 0, 7).get_price_in_quote(&exact, -12, Rounding::HalfEven)
+        );
+    }
+
+    #[test]
+    fn invert_errors() {
+        assert_eq!(
+            price(0, 0, 0, 0).invert(0, Rounding::Floor),
+            Err(GetPriceError::ZeroPrice)
+        );
+        assert_eq!(
+            price(-1, 0, 0, 0).invert(0, Rounding::Floor),
+            Err(GetPriceError::NegativePrice)
+        );
+        assert_eq!(
+            price(100, 100, 0, 0).invert(-4, Rounding::Floor),
+            Err(GetPriceError::ConfidenceTooWide {
+                conf_ratio_bps: 10_000
+            })
+        );
+        assert_eq!(
+            price(1, 0, -30, 0).invert(0, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+        // Too small to represent at the requested exponent
+        assert_eq!(
+            price(3, 0, 0, 0).invert(0, Rounding::Floor).unwrap().price,
+            0
+        );
+    }
+}
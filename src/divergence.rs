@@ -0,0 +1,184 @@
+use crate::{
+    error::{GetPriceError, Result},
+    math::{div_round, Rounding, BPS_DENOMINATOR},
+    price_update::{EmaPrice, Price},
+};
+
+/// `diff / denominator` in basis points, rounded up. Any difference relative to a zero denominator
+/// is reported as `u64::MAX`.
+fn diff_bps(diff: u128, denominator: u128) -> u64 {
+    if diff == 0 {
+        return 0;
+    }
+    if denominator == 0 {
+        return u64::MAX;
+    }
+    // Both fit in 64 bits, so neither the conversions nor the product can overflow
+    let bps = div_round(
+        diff as i128 * i128::from(BPS_DENOMINATOR),
+        denominator as i128,
+        Rounding::Ceil,
+    );
+    u64::try_from(bps).unwrap_or(u64::MAX)
+}
+
+impl Price {
+    /// `|price - ema_price|`. Both prices must come from the same update, so that they share an
+    /// exponent.
+    fn ema_diff(&self, ema: &EmaPrice) -> u128 {
+        (i128::from(self.price) - i128::from(ema.ema_price)).unsigned_abs()
+    }
+
+    /// How far the spot price is from the EMA price, relative to the EMA price:
+    /// `|price - ema_price| / |ema_price|` in basis points, rounded up. A large value means the
+    /// latest update is a spike that the EMA has not followed (yet).
+    ///
+    /// Any divergence from a zero EMA price is reported as `u64::MAX`.
+    pub fn ema_divergence_bps(&self, ema: &EmaPrice) -> u64 {
+        diff_bps(self.ema_diff(ema), ema.ema_price.unsigned_abs().into())
+    }
+
+    /// How far the spot price is from the EMA price, in multiples of the EMA confidence:
+    /// `|price - ema_price| / ema_conf` in basis points, rounded up. 10_000 means the spot price is
+    /// exactly at the edge of the EMA confidence interval.
+    ///
+    /// Any divergence from an EMA with zero confidence is reported as `u64::MAX`.
+    pub fn ema_divergence_conf_multiple_bps(&self, ema: &EmaPrice) -> u64 {
+        diff_bps(self.ema_diff(ema), ema.ema_conf.into())
+    }
+
+    /// Fails with `EmaDivergenceTooLarge` if [`Price::ema_divergence_bps`] exceeds
+    /// `max_divergence_bps`.
+    ///
+    /// ```
+    /// use pyth_min::{error::GetPriceError, price_update::{EmaPrice, Price}};
+    ///
+    /// let price = Price { price: 110, conf: 1, exponent: 0, publish_time: 0 };
+    /// let ema = EmaPrice { ema_price: 100, ema_conf: 2, exponent: 0, publish_time: 0 };
+    ///
+    /// assert!(price.check_ema_divergence(&ema, 1_000).is_ok());
+    /// assert_eq!(
+    ///     price.check_ema_divergence(&ema, 999),
+    ///     Err(GetPriceError::EmaDivergenceTooLarge { divergence_bps: 1_000 })
+    /// );
+    /// ```
+    pub fn check_ema_divergence(&self, ema: &EmaPrice, max_divergence_bps: u64) -> Result<()> {
+        let divergence_bps = self.ema_divergence_bps(ema);
+        if divergence_bps > max_divergence_bps {
+            return Err(GetPriceError::EmaDivergenceTooLarge { divergence_bps });
+        }
+        Ok(())
+    }
+
+    /// Fails with `EmaDivergenceOutsideConfidence` if the spot price is further from the EMA price
+    /// than `max_conf_multiple_bps / 10_000` times the EMA confidence, see
+    /// [`Price::ema_divergence_conf_multiple_bps`].
+    pub fn check_ema_divergence_conf_multiple(
+        &self,
+        ema: &EmaPrice,
+        max_conf_multiple_bps: u64,
+    ) -> Result<()> {
+        let conf_multiple_bps = self.ema_divergence_conf_multiple_bps(ema);
+        if conf_multiple_bps > max_conf_multiple_bps {
+            return Err(GetPriceError::EmaDivergenceOutsideConfidence { conf_multiple_bps });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ema_divergence_bps(price: i64, ema_price: i64) -> u64 {
+        let price = Price {
+            price,
+            conf: 0,
+            exponent: -2,
+            publish_time: 0,
+        };
+        let ema = EmaPrice {
+            ema_price,
+            ema_conf: 0,
+            exponent: -2,
+            publish_time: 0,
+        };
+        price.ema_divergence_bps(&ema)
+    }
+
+    fn ema_divergence_conf_multiple_bps(price: i64, ema_price: i64, ema_conf: u64) -> u64 {
+        let price = Price {
+            price,
+            conf: 0,
+            exponent: -2,
+            publish_time: 0,
+        };
+        let ema = EmaPrice {
+            ema_price,
+            ema_conf,
+            exponent: -2,
+            publish_time: 0,
+        };
+        price.ema_divergence_conf_multiple_bps(&ema)
+    }
+
+    #[test]
+    fn ema_divergence() {
+        assert_eq!(ema_divergence_bps(100, 100), 0);
+        assert_eq!(ema_divergence_bps(105, 100), 500);
+        assert_eq!(ema_divergence_bps(95, 100), 500);
+        assert_eq!(ema_divergence_bps(-95, -100), 500);
+        assert_eq!(ema_divergence_bps(1001, 1000), 10);
+        assert_eq!(ema_divergence_bps(10001, 10000), 1);
+        assert_eq!(ema_divergence_bps(1, 0), u64::MAX);
+        assert_eq!(ema_divergence_bps(i64::MAX, i64::MIN), 20000);
+    }
+
+    #[test]
+    fn ema_divergence_conf_multiple() {
+        assert_eq!(ema_divergence_conf_multiple_bps(100, 100, 0), 0);
+        assert_eq!(ema_divergence_conf_multiple_bps(105, 100, 2), 25_000);
+        assert_eq!(ema_divergence_conf_multiple_bps(95, 100, 10), 5_000);
+        assert_eq!(ema_divergence_conf_multiple_bps(-95, -100, 5), 10_000);
+        assert_eq!(ema_divergence_conf_multiple_bps(1001, 1000, 3), 3_334);
+        assert_eq!(ema_divergence_conf_multiple_bps(10001, 10000, 1), 10_000);
+        assert_eq!(ema_divergence_conf_multiple_bps(1, 0, 0), u64::MAX);
+        assert_eq!(
+            ema_divergence_conf_multiple_bps(i64::MAX, i64::MIN, u64::MAX),
+            10_000
+        );
+    }
+
+    #[test]
+    fn check_ema_divergence() {
+        let price = Price {
+            price: 103,
+            conf: 1,
+            exponent: 0,
+            publish_time: 0,
+        };
+        let ema = EmaPrice {
+            ema_price: 100,
+            ema_conf: 2,
+            exponent: 0,
+            publish_time: 0,
+        };
+
+        assert!(price.check_ema_divergence(&ema, 300).is_ok());
+        assert_eq!(
+            price.check_ema_divergence(&ema, 299),
+            Err(GetPriceError::EmaDivergenceTooLarge {
+                divergence_bps: 300
+            })
+        );
+        assert!(price
+            .check_ema_divergence_conf_multiple(&ema, 15_000)
+            .is_ok());
+        assert_eq!(
+            price.check_ema_divergence_conf_multiple(&ema, 14_999),
+            Err(GetPriceError::EmaDivergenceOutsideConfidence {
+                conf_multiple_bps: 15_000
+            })
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use core::fmt;
 
 /// Standard Rust errors (NOT ANCHOR ERRORS) with the same names and internal debug message as
 /// Pyth's expected Errors from the standard sdk. 
@@ -9,6 +9,30 @@ pub enum GetPriceError {
     InsufficientVerificationLevel,
     FeedIdMustBe32Bytes,
     FeedIdNonHexCharacter,
+    /// A fixed-point computation on a price overflowed its integer type
+    MathOverflow,
+    /// The price is negative where only non-negative prices make sense (e.g. conversion to u64)
+    NegativePrice,
+    /// The price is zero, so it cannot be divided by
+    ZeroPrice,
+    /// The confidence interval is wider than requested, relative to the price
+    ConfidenceTooWide { conf_ratio_bps: u64 },
+    /// The price was published further in the future than the allowed clock skew
+    PriceFromFuture,
+    /// The spot price diverges from the EMA price by more than allowed
+    EmaDivergenceTooLarge { divergence_bps: u64 },
+    /// The price graph has no room for another feed
+    PriceGraphFull,
+    /// No chain of feeds connects the requested assets
+    NoPriceRoute,
+    /// More price sources were given than can be aggregated at once
+    TooManyPriceSources,
+    /// Fewer price sources passed validation than required
+    NotEnoughPriceSources { used: u32 },
+    /// The spot price is further from the EMA price than allowed, in multiples of the EMA confidence
+    EmaDivergenceOutsideConfidence { conf_multiple_bps: u64 },
+    /// The price update was posted more slots ago than allowed
+    PostedSlotTooOld,
 }
 
 impl fmt::Display for GetPriceError {
@@ -19,10 +43,120 @@ impl fmt::Display for GetPriceError {
             GetPriceError::InsufficientVerificationLevel => write!(f, "This price feed update has a lower verification level than the one requested"),
             GetPriceError::FeedIdMustBe32Bytes => write!(f, "Feed id must be 32 Bytes, that's 64 hex characters or 66 with a 0x prefix"),
             GetPriceError::FeedIdNonHexCharacter => write!(f, "Feed id contains non-hex characters"),
+            GetPriceError::MathOverflow => write!(f, "Overflow while converting the price to the requested scale"),
+            GetPriceError::NegativePrice => write!(f, "The price is negative"),
+            GetPriceError::ZeroPrice => write!(f, "The price is zero"),
+            GetPriceError::ConfidenceTooWide { conf_ratio_bps } => write!(f, "The price confidence is {} bps of the price, more than the requested maximum", conf_ratio_bps),
+            GetPriceError::PriceFromFuture => write!(f, "This price feed update's publish time is further in the future than the allowed clock skew"),
+            GetPriceError::EmaDivergenceTooLarge { divergence_bps } => write!(f, "The price diverges from the EMA price by {} bps, more than the allowed maximum", divergence_bps),
+            GetPriceError::PriceGraphFull => write!(f, "The price graph is at capacity"),
+            GetPriceError::NoPriceRoute => write!(f, "No route of price feeds connects the requested assets"),
+            GetPriceError::TooManyPriceSources => write!(f, "Too many price sources to aggregate"),
+            GetPriceError::NotEnoughPriceSources { used } => write!(f, "Only {} price sources passed validation, fewer than required", used),
+            GetPriceError::EmaDivergenceOutsideConfidence { conf_multiple_bps } => write!(f, "The price diverges from the EMA price by {} bps of the EMA confidence, more than the allowed maximum", conf_multiple_bps),
+            GetPriceError::PostedSlotTooOld => write!(f, "This price feed update was posted more slots ago than the requested maximum"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for GetPriceError {}
 
-pub type Result<T> = std::result::Result<T, GetPriceError>;
\ No newline at end of file
+/// Errors returned when interpreting raw account or message bytes. Unlike the panicking
+/// `get_*_from_bytes` helpers, the `try_*` parsers surface these so a malformed account becomes a
+/// clean program error instead of aborting the transaction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParseError {
+    /// The input ended before the structure was complete.
+    Truncated { expected: usize, actual: usize },
+    /// The verification level tag was neither 0x00 (Partial) nor 0x01 (Full).
+    UnknownVerificationTag(u8),
+    /// The input contained more bytes than the structure can hold.
+    TrailingBytes { expected: usize, actual: usize },
+    /// The account data does not start with the expected Anchor discriminator.
+    InvalidDiscriminator,
+    /// The account is not owned by an allowed program.
+    InvalidOwner,
+    /// The data does not start with the expected magic bytes.
+    InvalidMagic,
+    /// The data uses a major version of its format this crate cannot decode.
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// The accumulator update type is not known.
+    UnknownUpdateType(u8),
+    /// The accumulator message type is not known.
+    UnknownMessageType(u8),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Truncated { expected, actual } => write!(f, "Input is truncated, expected at least {} bytes but got {}", expected, actual),
+            ParseError::UnknownVerificationTag(tag) => write!(f, "Unknown verification level tag {:#04x}", tag),
+            ParseError::TrailingBytes { expected, actual } => write!(f, "Input has trailing bytes, expected at most {} bytes but got {}", expected, actual),
+            ParseError::InvalidDiscriminator => write!(f, "Account discriminator does not match a PriceUpdateV2 account"),
+            ParseError::InvalidOwner => write!(f, "Account is not owned by an allowed program"),
+            ParseError::InvalidMagic => write!(f, "Data does not start with the expected magic bytes"),
+            ParseError::UnsupportedVersion { major, minor } => write!(f, "Unsupported format version {}.{}", major, minor),
+            ParseError::UnknownUpdateType(update_type) => write!(f, "Unknown accumulator update type {}", update_type),
+            ParseError::UnknownMessageType(message_type) => write!(f, "Unknown accumulator message type {}", message_type),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Errors returned when checking that a message is authentic, on top of the [`ParseError`]s of
+/// decoding it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VerifyError {
+    /// The data could not be decoded.
+    Parse(ParseError),
+    /// The Merkle proof does not lead to the expected root.
+    InvalidMerkleProof,
+    /// The message is authentic but is not a price feed message.
+    NotAPriceFeedMessage,
+    /// The VAA was not emitted by the Pythnet accumulator.
+    InvalidEmitter,
+    /// The VAA was signed by another guardian set than the one it is verified against.
+    GuardianSetMismatch { expected: u32, actual: u32 },
+    /// Guardian indices are not strictly increasing, so a guardian may have signed twice.
+    UnsortedSignatures,
+    /// A signature is not from the guardian at its index, or that index is not in the set.
+    InvalidGuardianSignature { guardian_index: u8 },
+    /// The requested quorum is lower than the one required by the guardian set.
+    QuorumTooLow { quorum: usize, minimum: usize },
+}
+
+impl From<ParseError> for VerifyError {
+    fn from(error: ParseError) -> Self {
+        VerifyError::Parse(error)
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Parse(error) => write!(f, "{}", error),
+            VerifyError::InvalidMerkleProof => write!(f, "Merkle proof does not match the root"),
+            VerifyError::NotAPriceFeedMessage => write!(f, "Message is not a price feed message"),
+            VerifyError::InvalidEmitter => write!(f, "VAA was not emitted by the Pythnet accumulator"),
+            VerifyError::GuardianSetMismatch { expected, actual } => write!(f, "VAA was signed by guardian set {}, expected {}", actual, expected),
+            VerifyError::UnsortedSignatures => write!(f, "VAA signatures are not sorted by guardian index"),
+            VerifyError::InvalidGuardianSignature { guardian_index } => write!(f, "Invalid signature for guardian {}", guardian_index),
+            VerifyError::QuorumTooLow { quorum, minimum } => write!(f, "Quorum of {} signatures is below the guardian set quorum of {}", quorum, minimum),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, GetPriceError>;
@@ -0,0 +1,324 @@
+use crate::{
+    error::{GetPriceError, Result},
+    math::Rounding,
+    policy::PriceValidationPolicy,
+    price_update::Price,
+    source::PriceSource,
+};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Edge<'a> {
+    base: &'a str,
+    quote: &'a str,
+    price: Price,
+}
+
+/// A price derived by chaining several feeds, see [`PriceGraph::quote`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RoutedPrice {
+    /// The derived price. Its `publish_time` is the one of the stalest feed on the route.
+    pub price: Price,
+    /// How many feeds were chained
+    pub hops: usize,
+}
+
+/// A fixed-capacity set of up to `N` prices keyed by (base, quote) symbol pair, which can price any
+/// pair of assets connected by a chain of feeds. For example, loaded with JITO/USD and SOL/USD it
+/// can quote JITO/SOL and SOL/JITO.
+///
+/// The graph never allocates, so it can be used on-chain.
+///
+/// ```
+/// use pyth_min::{graph::PriceGraph, math::Rounding, price_update::Price};
+///
+/// let mut graph = PriceGraph::<4>::new();
+/// graph.insert_price("JITO", "USD", Price { price: 300, conf: 3, exponent: -2, publish_time: 10 }).unwrap();
+/// graph.insert_price("SOL", "USD", Price { price: 15000, conf: 0, exponent: -2, publish_time: 12 }).unwrap();
+///
+/// let jito_sol = graph.quote("JITO", "SOL", -4, Rounding::HalfEven).unwrap();
+/// assert_eq!((jito_sol.price.price, jito_sol.hops, jito_sol.price.publish_time), (200, 2, 10));
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PriceGraph<'a, const N: usize> {
+    edges: [Option<Edge<'a>>; N],
+    len: usize,
+}
+
+impl<'a, const N: usize> Default for PriceGraph<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> PriceGraph<'a, N> {
+    pub const fn new() -> Self {
+        PriceGraph {
+            edges: [None; N],
+            len: 0,
+        }
+    }
+
+    /// The number of feeds loaded in the graph
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add the price of `base` in units of `quote`. The price is trusted as is, use
+    /// [`PriceGraph::insert_update`] to validate it first. Replaces any previous price for the same
+    /// pair. Fails with `PriceGraphFull` if `N` pairs are already loaded.
+    pub fn insert_price(&mut self, base: &'a str, quote: &'a str, price: Price) -> Result<()> {
+        let edge = Edge { base, quote, price };
+        if let Some(existing) = self.edges[..self.len]
+            .iter_mut()
+            .flatten()
+            .find(|e| e.base == base && e.quote == quote)
+        {
+            *existing = edge;
+            return Ok(());
+        }
+        if self.len == N {
+            return Err(GetPriceError::PriceGraphFull);
+        }
+        self.edges[self.len] = Some(edge);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Validate `price_update` against `policy` at unix time `now`, then add its price as the price
+    /// of `base` in units of `quote`.
+    pub fn insert_update<S: PriceSource + ?Sized>(
+        &mut self,
+        base: &'a str,
+        quote: &'a str,
+        price_update: &S,
+        policy: &PriceValidationPolicy,
+        now: i64,
+    ) -> Result<()> {
+        let price = policy.validate(price_update, now)?;
+        self.insert_price(base, quote, price)
+    }
+
+    /// The price of `asset_a` in units of `asset_b`, derived along the shortest chain of feeds
+    /// connecting them. Each feed can be used in either direction.
+    ///
+    /// Every hop is expressed with `result_exponent` and rounded with `rounding`, so pick an
+    /// exponent with enough precision for the intermediate prices too. Confidences compose as in
+    /// [`Price::mul_price`] and [`Price::get_price_in_quote`].
+    ///
+    /// Fails with `NoPriceRoute` if no chain of feeds connects the two assets, including when they
+    /// are the same asset, as no feed backs that price or its publish time.
+    pub fn quote(
+        &self,
+        asset_a: &str,
+        asset_b: &str,
+        result_exponent: i32,
+        rounding: Rounding,
+    ) -> Result<RoutedPrice> {
+        if asset_a == asset_b {
+            return Err(GetPriceError::NoPriceRoute);
+        }
+
+        let mut path = [(0usize, true); N];
+        let hops = self.shortest_path(asset_a, asset_b, &mut path)?;
+        // Start from exactly 1, whose publish time gives way to the first feed's
+        let mut price = Price {
+            price: 1,
+            conf: 0,
+            exponent: 0,
+            publish_time: i64::MAX,
+        }
+        .scale_to_exponent(result_exponent, rounding)?;
+        // `price` is the price of asset_a in units of the last asset reached
+        for &(i, forward) in &path[..hops] {
+            let edge = self.edges[i].as_ref().ok_or(GetPriceError::NoPriceRoute)?;
+            price = if forward {
+                price.mul_price(&edge.price, result_exponent, rounding)?
+            } else {
+                price.get_price_in_quote(&edge.price, result_exponent, rounding)?
+            };
+        }
+
+        Ok(RoutedPrice { price, hops })
+    }
+
+    /// Breadth-first search over the feeds. Writes the route from `from` to `to` into `path` as
+    /// (feed index, whether it is used from base to quote) and returns its length.
+    fn shortest_path(&self, from: &str, to: &str, path: &mut [(usize, bool); N]) -> Result<usize> {
+        // For every visited feed, the feed it was reached from and its direction
+        let mut visited: [Option<(Option<usize>, bool)>; N] = [None; N];
+        let mut queue = [0usize; N];
+        let (mut head, mut tail) = (0, 0);
+        // The feed whose far end is being expanded, `None` for the starting asset
+        let mut current: Option<usize> = None;
+
+        let last = 'search: loop {
+            let asset = match current {
+                None => from,
+                Some(i) => match (&self.edges[i], visited[i]) {
+                    (Some(edge), Some((_, true))) => edge.quote,
+                    (Some(edge), _) => edge.base,
+                    (None, _) => return Err(GetPriceError::NoPriceRoute),
+                },
+            };
+            for (i, edge) in self.edges[..self.len].iter().enumerate() {
+                let Some(edge) = edge else { continue };
+                if visited[i].is_some() {
+                    continue;
+                }
+                let forward = edge.base == asset;
+                if !forward && edge.quote != asset {
+                    continue;
+                }
+                visited[i] = Some((current, forward));
+                if (forward && edge.quote == to) || (!forward && edge.base == to) {
+                    break 'search i;
+                }
+                queue[tail] = i;
+                tail += 1;
+            }
+            if head == tail {
+                return Err(GetPriceError::NoPriceRoute);
+            }
+            current = Some(queue[head]);
+            head += 1;
+        };
+
+        // Walk back from the feed that reached `to`, then reverse into route order
+        let mut hops = 0;
+        let mut current = Some(last);
+        while let Some(i) = current {
+            let (parent, forward) = visited[i].ok_or(GetPriceError::NoPriceRoute)?;
+            path[hops] = (i, forward);
+            hops += 1;
+            current = parent;
+        }
+        path[..hops].reverse();
+        Ok(hops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{price, MockPriceUpdate};
+
+    use super::*;
+
+    #[test]
+    fn quote_direct_and_reverse() {
+        let mut graph = PriceGraph::<4>::new();
+        graph
+            .insert_price("SOL", "USD", price(15000, 150, -2, 10))
+            .unwrap();
+
+        let sol_usd = graph.quote("SOL", "USD", -2, Rounding::HalfEven).unwrap();
+        assert_eq!(sol_usd.price, price(15000, 150, -2, 10));
+        assert_eq!(sol_usd.hops, 1);
+
+        let usd_sol = graph.quote("USD", "SOL", -6, Rounding::HalfEven).unwrap();
+        assert_eq!(usd_sol.price, price(6667, 67, -6, 10));
+        assert_eq!(usd_sol.hops, 1);
+
+        assert_eq!(
+            graph.quote("SOL", "SOL", -2, Rounding::HalfEven),
+            Err(GetPriceError::NoPriceRoute)
+        );
+    }
+
+    #[test]
+    fn quote_multi_hop_uses_shortest_route() {
+        let mut graph = PriceGraph::<8>::new();
+        graph
+            .insert_price("JITO", "USD", price(300, 3, -2, 100))
+            .unwrap();
+        graph
+            .insert_price("SOL", "USD", price(15000, 300, -2, 90))
+            .unwrap();
+        graph
+            .insert_price("BONK", "SOL", price(2, 0, -6, 95))
+            .unwrap();
+        graph
+            .insert_price("JITO", "EUR", price(270, 0, -2, 80))
+            .unwrap();
+        graph
+            .insert_price("EUR", "BONK", price(1, 0, 0, 70))
+            .unwrap();
+
+        // BONK -> EUR -> JITO rather than BONK -> SOL -> USD -> JITO
+        let route = graph.quote("BONK", "JITO", -8, Rounding::HalfEven).unwrap();
+        assert_eq!(route.hops, 2);
+        assert_eq!(route.price.publish_time, 70);
+
+        let jito_sol = graph.quote("JITO", "SOL", -4, Rounding::HalfEven).unwrap();
+        assert_eq!(jito_sol.hops, 2);
+        assert_eq!(jito_sol.price, price(200, 6, -4, 90));
+
+        assert_eq!(
+            graph.quote("JITO", "BTC", -4, Rounding::HalfEven),
+            Err(GetPriceError::NoPriceRoute)
+        );
+    }
+
+    #[test]
+    fn insert_replaces_and_fills() {
+        let mut graph = PriceGraph::<2>::new();
+        graph.insert_price("A", "B", price(1, 0, 0, 0)).unwrap();
+        graph.insert_price("A", "B", price(2, 0, 0, 0)).unwrap();
+        assert_eq!(graph.len(), 1);
+        assert_eq!(
+            graph
+                .quote("A", "B", 0, Rounding::Floor)
+                .unwrap()
+                .price
+                .price,
+            2
+        );
+
+        graph.insert_price("B", "C", price(3, 0, 0, 0)).unwrap();
+        assert_eq!(
+            graph.insert_price("C", "D", price(4, 0, 0, 0)),
+            Err(GetPriceError::PriceGraphFull)
+        );
+        assert_eq!(
+            graph
+                .quote("A", "C", 0, Rounding::Floor)
+                .unwrap()
+                .price
+                .price,
+            6
+        );
+    }
+
+    #[test]
+    fn insert_update_validates() {
+        let now = 1_700_000_000;
+        let policy = PriceValidationPolicy::new(30);
+        let mut graph = PriceGraph::<2>::new();
+
+        assert_eq!(
+            graph.insert_update(
+                "SOL",
+                "USD",
+                &MockPriceUpdate::new().age_seconds(now, 31).build(),
+                &policy,
+                now
+            ),
+            Err(GetPriceError::PriceTooOld)
+        );
+        assert!(graph.is_empty());
+
+        graph
+            .insert_update(
+                "SOL",
+                "USD",
+                &MockPriceUpdate::new().age_seconds(now, 5).build(),
+                &policy,
+                now,
+            )
+            .unwrap();
+        assert_eq!(graph.len(), 1);
+    }
+}
@@ -0,0 +1,233 @@
+//! A dependency-free Keccak-256, the hash used by Wormhole and the Pythnet accumulator. This is the
+//! original Keccak submission as used by Ethereum, not the standardized SHA3-256, which pads
+//! differently.
+
+const RATE: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation of every lane in the order they are visited by the combined rho and pi steps
+const ROTATIONS: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lane visited after each lane by the combined rho and pi steps
+const PI_LANES: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // Theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // Rho and pi
+        let mut lane = state[1];
+        for (rotation, target) in ROTATIONS.iter().zip(PI_LANES) {
+            let next = state[target];
+            state[target] = lane.rotate_left(*rotation);
+            lane = next;
+        }
+
+        // Chi
+        for y in 0..5 {
+            let row = [
+                state[5 * y],
+                state[5 * y + 1],
+                state[5 * y + 2],
+                state[5 * y + 3],
+                state[5 * y + 4],
+            ];
+            for x in 0..5 {
+                state[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        state[0] ^= round_constant;
+    }
+}
+
+/// Incremental Keccak-256, for hashing data that is not contiguous in memory.
+///
+/// ```
+/// use pyth_min::keccak::{keccak256, Keccak256};
+///
+/// let mut hasher = Keccak256::new();
+/// hasher.update(b"ab");
+/// hasher.update(b"c");
+/// assert_eq!(hasher.finalize(), keccak256(b"abc"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Keccak256 {
+    state: [u64; 25],
+    buffer: [u8; RATE],
+    buffered: usize,
+}
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keccak256 {
+    pub const fn new() -> Self {
+        Keccak256 {
+            state: [0; 25],
+            buffer: [0; RATE],
+            buffered: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let len = data.len().min(RATE - self.buffered);
+            self.buffer[self.buffered..self.buffered + len].copy_from_slice(&data[..len]);
+            self.buffered += len;
+            data = &data[len..];
+            if self.buffered == RATE {
+                self.absorb_buffer();
+            }
+        }
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        self.finalize_with_delimiter(0x01)
+    }
+
+    fn absorb_buffer(&mut self) {
+        for (lane, bytes) in self.state.iter_mut().zip(self.buffer.chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(bytes);
+            *lane ^= u64::from_le_bytes(le);
+        }
+        keccak_f(&mut self.state);
+        self.buffered = 0;
+    }
+
+    /// Pad with `delimiter`, the domain separation bits followed by the first bit of the padding
+    fn finalize_with_delimiter(mut self, delimiter: u8) -> [u8; 32] {
+        self.buffer[self.buffered..].fill(0);
+        self.buffer[self.buffered] = delimiter;
+        self.buffer[RATE - 1] |= 0x80;
+        self.absorb_buffer();
+
+        let mut hash = [0u8; 32];
+        for (bytes, lane) in hash.chunks_exact_mut(8).zip(self.state) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// The Keccak-256 hash of `data`
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_utils::hex_to_bytes;
+
+    use super::*;
+
+    #[test]
+    fn keccak256_vectors() {
+        for (data, hash) in [
+            (
+                &b""[..],
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                b"abc",
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            ),
+            (
+                b"The quick brown fox jumps over the lazy dog",
+                "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15",
+            ),
+        ] {
+            assert_eq!(keccak256(data).to_vec(), hex_to_bytes(hash));
+        }
+    }
+
+    #[test]
+    fn multi_block_inputs() {
+        // SHA3-256 only differs by its padding, which exercises block boundaries against the
+        // reference values of any standard library
+        let sha3 = |data: &[u8]| {
+            let mut hasher = Keccak256::new();
+            hasher.update(data);
+            hasher.finalize_with_delimiter(0x06).to_vec()
+        };
+        let counting: Vec<u8> = (0..512).map(|i| i as u8).collect();
+        for (data, hash) in [
+            (
+                &[][..],
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                &[b'a'; RATE - 1],
+                "8094bb53c44cfb1e67b7c30447f9a1c33696d2463ecc1d9c92538913392843c9",
+            ),
+            (
+                &[b'a'; RATE],
+                "3fc5559f14db8e453a0a3091edbd2bc25e11528d81c66fa570a4efdcc2695ee1",
+            ),
+            (
+                &counting,
+                "d4728ea5e9f3819f2b4760151a8f802dbe9f941fd6fb59b3715892436555772a",
+            ),
+        ] {
+            assert_eq!(sha3(data), hex_to_bytes(hash));
+        }
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+        for split in [0, 1, 135, 136, 137, 500, 1000] {
+            let mut hasher = Keccak256::new();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            assert_eq!(hasher.finalize(), keccak256(&data));
+        }
+    }
+}
@@ -1,7 +1,28 @@
+//! Minimal, dependency-free sdk for consuming Pyth pull oracle prices on Solana.
+//!
+//! The crate is `#![no_std]` unless the `std` feature is enabled, which adds
+//! `std::error::Error` implementations and allocation-based helpers.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+pub mod accumulator;
+pub mod aggregate;
 pub mod byte_utils;
+pub mod cross_rate;
+pub mod divergence;
 pub mod error;
+pub mod graph;
+pub mod keccak;
+pub mod math;
 pub mod messages;
+pub mod policy;
 pub mod price_update;
+pub mod price_update_ref;
+pub mod secp256k1;
+pub mod source;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod valuation;
+pub mod wormhole;
 
 pub(crate) type Pubkey = [u8; 32];
 
@@ -22,6 +43,10 @@ pub const ACCUMULATOR_EMITTER_ADDRESS: Pubkey = [
 /// Official Program IDs and Addresses on Pythnet
 pub mod pythnet {
     use super::Pubkey;
+
+    /// Wormhole chain id of Pythnet
+    pub const CHAIN_ID: u16 = 26;
+
     /// Official Wormhole Program Address on Pythnet
     /// pubkey!("H3fxXJ86ADW2PNuDDmZJg6mzTtPxkYCpNuQUTgmJ7AjU");
     pub const WORMHOLE_PID: Pubkey = [
@@ -0,0 +1,322 @@
+use core::cmp::Ordering;
+
+use crate::{
+    error::{GetPriceError, Result},
+    price_update::{EmaPrice, Price},
+};
+
+/// How to round when a fixed-point conversion loses precision.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Round to the nearest value, ties go to the even neighbour (banker's rounding)
+    HalfEven,
+}
+
+/// Basis points in one whole, e.g. a confidence multiplier of `BPS_DENOMINATOR` is 1x.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// `10^exp` as an i128, or `None` if it does not fit.
+pub(crate) fn pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+/// Divide `n` by a strictly positive `d`, rounding the quotient as requested.
+pub(crate) fn div_round(n: i128, d: i128, rounding: Rounding) -> i128 {
+    debug_assert!(d > 0);
+    let q = n / d;
+    let r = n % d;
+    if r == 0 {
+        return q;
+    }
+    match rounding {
+        Rounding::Floor if r < 0 => q - 1,
+        Rounding::Ceil if r > 0 => q + 1,
+        Rounding::Floor | Rounding::Ceil => q,
+        Rounding::HalfEven => {
+            let r = r.unsigned_abs();
+            // Compare r with d - r rather than 2r with d, which could overflow
+            match r.cmp(&(d.unsigned_abs() - r)) {
+                Ordering::Less => q,
+                Ordering::Equal if q % 2 == 0 => q,
+                _ => q + n.signum(),
+            }
+        }
+    }
+}
+
+/// Compute `n * 10^exp / d` for a strictly positive `d`, rounding only once at the end.
+pub(crate) fn mul_pow10_div(n: i128, exp: i64, d: i128, rounding: Rounding) -> Result<i128> {
+    debug_assert!(d > 0);
+    if n == 0 {
+        return Ok(0);
+    }
+    if exp >= 0 {
+        return mul_pow10_div_exact(n, exp, d, rounding);
+    }
+
+    let factor = u32::try_from(exp.unsigned_abs()).ok().and_then(pow10);
+    match factor.and_then(|factor| d.checked_mul(factor)) {
+        Some(d) => Ok(div_round(n, d, rounding)),
+        None => {
+            // The divisor exceeds i128::MAX, so the quotient is 0 and only the rounding direction
+            // matters. For half-even, compare 2|n| with the divisor in u128 instead.
+            let twice_n = n.unsigned_abs().saturating_mul(2);
+            let past_half = factor
+                .and_then(|factor| d.unsigned_abs().checked_mul(factor.unsigned_abs()))
+                .is_some_and(|d| twice_n > d);
+            Ok(match rounding {
+                Rounding::Floor if n < 0 => -1,
+                Rounding::Ceil if n > 0 => 1,
+                Rounding::HalfEven if past_half => n.signum(),
+                _ => 0,
+            })
+        }
+    }
+}
+
+/// [`mul_pow10_div`] for `exp >= 0`. Cancels the powers of ten shared by `10^exp` and `d`, and
+/// splits off the integer part of `n / d` if needed, so this only overflows with the result.
+fn mul_pow10_div_exact(n: i128, mut exp: i64, mut d: i128, rounding: Rounding) -> Result<i128> {
+    while exp > 0 && d % 10 == 0 {
+        exp -= 1;
+        d /= 10;
+    }
+    let factor = u32::try_from(exp)
+        .ok()
+        .and_then(pow10)
+        .ok_or(GetPriceError::MathOverflow)?;
+    if let Some(n) = n.checked_mul(factor) {
+        return Ok(div_round(n, d, rounding));
+    }
+
+    // exp > 0 here, so the integer part is a multiple of an even number and adding it does not
+    // change how the remainder rounds, including for half-even
+    let integer = (n / d).checked_mul(factor);
+    let fraction = (n % d)
+        .checked_mul(factor)
+        .map(|r| div_round(r, d, rounding));
+    integer
+        .zip(fraction)
+        .and_then(|(integer, fraction)| integer.checked_add(fraction))
+        .ok_or(GetPriceError::MathOverflow)
+}
+
+/// Convert `value * 10^from` into the closest representable `x * 10^to`, i.e. return `x`.
+pub(crate) fn rescale(value: i128, from: i32, to: i32, rounding: Rounding) -> Result<i128> {
+    mul_pow10_div(value, i64::from(from) - i64::from(to), 1, rounding)
+}
+
+/// Rescale a price and its confidence. The confidence is always rounded up, so that the
+/// confidence interval never shrinks because of the conversion.
+fn scale_parts(
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    target_exponent: i32,
+    rounding: Rounding,
+) -> Result<(i64, u64)> {
+    let price = rescale(price.into(), exponent, target_exponent, rounding)?;
+    let conf = rescale(conf.into(), exponent, target_exponent, Rounding::Ceil)?;
+    Ok((
+        i64::try_from(price).map_err(|_| GetPriceError::MathOverflow)?,
+        u64::try_from(conf).map_err(|_| GetPriceError::MathOverflow)?,
+    ))
+}
+
+fn to_u64_with_decimals(price: i64, exponent: i32, decimals: u8) -> Result<u64> {
+    if price < 0 {
+        return Err(GetPriceError::NegativePrice);
+    }
+    let value = rescale(price.into(), exponent, -i32::from(decimals), Rounding::Floor)?;
+    u64::try_from(value).map_err(|_| GetPriceError::MathOverflow)
+}
+
+impl Price {
+    /// Express this price with `target_exponent`, i.e. as `(price ± conf) * 10^target_exponent`.
+    ///
+    /// `price` is rounded with `rounding` when precision is lost. `conf` is always rounded up so
+    /// the confidence interval is never understated. Fails with `MathOverflow` if the result does
+    /// not fit.
+    ///
+    /// ```
+    /// use pyth_min::{math::Rounding, price_update::Price};
+    ///
+    /// let price = Price { price: 16706469648, conf: 22390601, exponent: -8, publish_time: 0 };
+    /// let scaled = price.scale_to_exponent(-6, Rounding::HalfEven).unwrap();
+    /// assert_eq!((scaled.price, scaled.conf, scaled.exponent), (167064696, 223907, -6));
+    /// ```
+    pub fn scale_to_exponent(&self, target_exponent: i32, rounding: Rounding) -> Result<Price> {
+        let (price, conf) = scale_parts(
+            self.price,
+            self.conf,
+            self.exponent,
+            target_exponent,
+            rounding,
+        )?;
+        Ok(Price {
+            price,
+            conf,
+            exponent: target_exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    /// The price as an integer with `decimals` decimal places (e.g. the number of decimals of a
+    /// mint), rounded down. Fails with `NegativePrice` if the price is negative.
+    pub fn to_u64_with_decimals(&self, decimals: u8) -> Result<u64> {
+        to_u64_with_decimals(self.price, self.exponent, decimals)
+    }
+}
+
+impl EmaPrice {
+    /// Express this EMA price with `target_exponent`. See [`Price::scale_to_exponent`].
+    pub fn scale_to_exponent(&self, target_exponent: i32, rounding: Rounding) -> Result<EmaPrice> {
+        let (ema_price, ema_conf) = scale_parts(
+            self.ema_price,
+            self.ema_conf,
+            self.exponent,
+            target_exponent,
+            rounding,
+        )?;
+        Ok(EmaPrice {
+            ema_price,
+            ema_conf,
+            exponent: target_exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    /// The EMA price as an integer with `decimals` decimal places, rounded down. See
+    /// [`Price::to_u64_with_decimals`].
+    pub fn to_u64_with_decimals(&self, decimals: u8) -> Result<u64> {
+        to_u64_with_decimals(self.ema_price, self.exponent, decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::price;
+
+    use super::*;
+
+    #[test]
+    fn div_round_modes() {
+        let cases = [
+            // (n, floor, ceil, half even) for n / 10
+            (25, 2, 3, 2),
+            (35, 3, 4, 4),
+            (-25, -3, -2, -2),
+            (-35, -4, -3, -4),
+            (26, 2, 3, 3),
+            (-24, -3, -2, -2),
+            (30, 3, 3, 3),
+        ];
+        for (n, floor, ceil, half_even) in cases {
+            assert_eq!(div_round(n, 10, Rounding::Floor), floor, "floor {}", n);
+            assert_eq!(div_round(n, 10, Rounding::Ceil), ceil, "ceil {}", n);
+            assert_eq!(div_round(n, 10, Rounding::HalfEven), half_even, "half even {}", n);
+        }
+    }
+
+    #[test]
+    fn scale_down_rounds_price_and_ceils_conf() {
+        let p = price(16706469648, 22390601, -8, 0);
+
+        let floor = p.scale_to_exponent(-6, Rounding::Floor).unwrap();
+        assert_eq!((floor.price, floor.conf, floor.exponent), (167064696, 223907, -6));
+        let ceil = p.scale_to_exponent(-6, Rounding::Ceil).unwrap();
+        assert_eq!(ceil.price, 167064697);
+
+        let negative = price(-16706469648, 0, -8, 0);
+        assert_eq!(
+            negative.scale_to_exponent(-6, Rounding::Floor).unwrap().price,
+            -167064697
+        );
+        assert_eq!(
+            negative.scale_to_exponent(-6, Rounding::HalfEven).unwrap().price,
+            -167064696
+        );
+    }
+
+    #[test]
+    fn scale_up_is_exact_or_overflows() {
+        let p = price(1, 1, 0, 0);
+        let scaled = p.scale_to_exponent(-18, Rounding::Floor).unwrap();
+        assert_eq!((scaled.price, scaled.conf), (10i64.pow(18), 10u64.pow(18)));
+
+        assert_eq!(
+            p.scale_to_exponent(-19, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+        assert_eq!(
+            price(1, 0, i32::MAX, 0).scale_to_exponent(i32::MIN, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+        assert_eq!(
+            price(0, 0, i32::MAX, 0)
+                .scale_to_exponent(i32::MIN, Rounding::Floor)
+                .unwrap()
+                .price,
+            0
+        );
+    }
+
+    #[test]
+    fn scale_far_down_only_keeps_rounding_direction() {
+        let p = price(i64::MIN, u64::MAX, i32::MIN, 0);
+        let floor = p.scale_to_exponent(i32::MAX, Rounding::Floor).unwrap();
+        assert_eq!((floor.price, floor.conf), (-1, 1));
+        let ceil = p.scale_to_exponent(i32::MAX, Rounding::Ceil).unwrap();
+        assert_eq!(ceil.price, 0);
+        let half_even = p.scale_to_exponent(40, Rounding::HalfEven).unwrap();
+        assert_eq!(half_even.price, 0);
+    }
+
+    #[test]
+    fn mul_pow10_div_only_overflows_with_the_result() {
+        // 10^30 * 10^10 does not fit in an i128, but the quotient does
+        let (n, d) = (10i128.pow(30), 3_000_000_000_007);
+        let q = 3333333333325555555555573703;
+        assert_eq!(mul_pow10_div(n, 10, d, Rounding::Floor), Ok(q));
+        assert_eq!(mul_pow10_div(n, 10, d, Rounding::Ceil), Ok(q + 1));
+        assert_eq!(mul_pow10_div(n, 10, d, Rounding::HalfEven), Ok(q + 1));
+        assert_eq!(mul_pow10_div(-n, 10, d, Rounding::Floor), Ok(-q - 1));
+        assert_eq!(mul_pow10_div(-n, 10, d, Rounding::Ceil), Ok(-q));
+
+        assert_eq!(
+            mul_pow10_div(n, 10, 10i128.pow(12), Rounding::Floor),
+            Ok(10i128.pow(28))
+        );
+        assert_eq!(
+            mul_pow10_div(i128::MAX, 1, 3, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn to_u64_with_decimals() {
+        let p = price(16706469648, 22390601, -8, 0);
+        assert_eq!(p.to_u64_with_decimals(6), Ok(167064696));
+        assert_eq!(p.to_u64_with_decimals(10), Ok(1670646964800));
+        assert_eq!(p.to_u64_with_decimals(0), Ok(167));
+        assert_eq!(
+            price(-1, 0, -8, 0).to_u64_with_decimals(6),
+            Err(GetPriceError::NegativePrice)
+        );
+        assert_eq!(p.to_u64_with_decimals(30), Err(GetPriceError::MathOverflow));
+
+        let ema = EmaPrice {
+            ema_price: 16863708300,
+            ema_conf: 16979099,
+            exponent: -8,
+            publish_time: 0,
+        };
+        assert_eq!(ema.to_u64_with_decimals(2), Ok(16863));
+        let scaled = ema.scale_to_exponent(-2, Rounding::HalfEven).unwrap();
+        assert_eq!((scaled.ema_price, scaled.ema_conf), (16864, 17));
+    }
+}
@@ -1,4 +1,10 @@
-use crate::byte_utils::{interpret_bytes_as_i32, interpret_bytes_as_i64, interpret_bytes_as_u64};
+use crate::{
+    byte_utils::{
+        interpret_bytes_as_i32, interpret_bytes_as_i64, interpret_bytes_as_u64, PubkeyBytes,
+        Reader, Writer,
+    },
+    error::ParseError,
+};
 
 /// Id of a feed producing the message. One feed produces one or more messages.
 pub type FeedId = [u8; 32];
@@ -30,6 +36,9 @@ pub struct PriceFeedMessage {
 }
 
 impl PriceFeedMessage {
+    /// Serialized length of a PriceFeedMessage, without any Rust struct padding.
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 8 + 8 + 8 + 8;
+
     /// Interpret a PriceFeedMessage from a byte slice (which must be exactly 84 bytes long with no
     /// padding, but is really 88 bytes after Rust struct padding). This is useful if you want to
     /// read price/confidence with no checks for verification or how recent the update was.
@@ -43,8 +52,29 @@ impl PriceFeedMessage {
     /// the message is also padding.
     ///
     /// `let message_bytes = &data[41..125];` or `&data[42..126];`
+    ///
+    /// # Panics
+    /// If `v` is not exactly [`PriceFeedMessage::LEN`] bytes. Use
+    /// [`PriceFeedMessage::try_get_feed_from_bytes`] to handle this as an error instead.
     pub fn get_feed_from_bytes(v: &[u8]) -> PriceFeedMessage {
-         assert!(v.len() == 84);
+        Self::try_get_feed_from_bytes(v).expect("invalid PriceFeedMessage bytes")
+    }
+
+    /// Fallible version of [`PriceFeedMessage::get_feed_from_bytes`]. Fails if `v` is not exactly
+    /// [`PriceFeedMessage::LEN`] bytes long.
+    pub fn try_get_feed_from_bytes(v: &[u8]) -> Result<PriceFeedMessage, ParseError> {
+        if v.len() < Self::LEN {
+            return Err(ParseError::Truncated {
+                expected: Self::LEN,
+                actual: v.len(),
+            });
+        }
+        if v.len() > Self::LEN {
+            return Err(ParseError::TrailingBytes {
+                expected: Self::LEN,
+                actual: v.len(),
+            });
+        }
 
         let feed_id: FeedId = {
             let mut arr = [0u8; 32];
@@ -59,7 +89,7 @@ impl PriceFeedMessage {
         let ema_price = interpret_bytes_as_i64(&v[68..76]);
         let ema_conf = interpret_bytes_as_u64(&v[76..84]);
 
-        PriceFeedMessage {
+        Ok(PriceFeedMessage {
             feed_id,
             price,
             conf,
@@ -68,8 +98,209 @@ impl PriceFeedMessage {
             prev_publish_time,
             ema_price,
             ema_conf,
+        })
+    }
+
+    /// Serialize into the same little-endian layout read by
+    /// [`PriceFeedMessage::get_feed_from_bytes`].
+    pub fn to_bytes(&self) -> [u8; PriceFeedMessage::LEN] {
+        let mut v = [0u8; PriceFeedMessage::LEN];
+        v[0..32].copy_from_slice(&self.feed_id);
+        v[32..40].copy_from_slice(&self.price.to_le_bytes());
+        v[40..48].copy_from_slice(&self.conf.to_le_bytes());
+        v[48..52].copy_from_slice(&self.exponent.to_le_bytes());
+        v[52..60].copy_from_slice(&self.publish_time.to_le_bytes());
+        v[60..68].copy_from_slice(&self.prev_publish_time.to_le_bytes());
+        v[68..76].copy_from_slice(&self.ema_price.to_le_bytes());
+        v[76..84].copy_from_slice(&self.ema_conf.to_le_bytes());
+        v
+    }
+}
+
+impl TryFrom<&[u8]> for PriceFeedMessage {
+    type Error = ParseError;
+
+    fn try_from(v: &[u8]) -> Result<Self, Self::Error> {
+        PriceFeedMessage::try_get_feed_from_bytes(v)
+    }
+}
+
+/// Time-weighted average price inputs. The TWAP between two messages of the same feed is the
+/// difference of their cumulative prices divided by the number of slots between them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TwapMessage {
+    pub feed_id: FeedId,
+    /// Sum of the price at every slot since the feed started
+    pub cumulative_price: i128,
+    /// Sum of the confidence at every slot since the feed started
+    pub cumulative_conf: u128,
+    /// Number of slots in which the price could not be aggregated
+    pub num_down_slots: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub publish_slot: u64,
+}
+
+/// The stake cap of one publisher
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PublisherStakeCap {
+    pub publisher: PubkeyBytes,
+    pub cap: u64,
+}
+
+/// The stake caps of every publisher, borrowed from the encoded message.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PublisherStakeCapsMessage<'a> {
+    pub publish_time: i64,
+    caps: &'a [u8],
+}
+
+impl<'a> PublisherStakeCapsMessage<'a> {
+    const CAP_LEN: usize = 32 + 8;
+
+    pub fn caps(&self) -> impl ExactSizeIterator<Item = PublisherStakeCap> + 'a {
+        // Every chunk is exactly CAP_LEN bytes, so every index is in bounds
+        self.caps
+            .chunks_exact(Self::CAP_LEN)
+            .map(|cap| PublisherStakeCap {
+                publisher: core::array::from_fn(|i| cap[i]),
+                cap: u64::from_be_bytes(core::array::from_fn(|i| cap[32 + i])),
+            })
+    }
+}
+
+/// A message produced by Pythnet and carried by accumulator updates, see
+/// [`crate::accumulator`].
+///
+/// Unlike the account layout read by [`PriceFeedMessage::get_feed_from_bytes`], these messages are
+/// big-endian and start with a one byte message type.
+///
+/// ```
+/// use pyth_min::messages::{Message, PriceFeedMessage};
+///
+/// let message = Message::PriceFeed(PriceFeedMessage {
+///     feed_id: [1u8; 32],
+///     price: 16706469648,
+///     conf: 22390601,
+///     exponent: -8,
+///     publish_time: 1717782833,
+///     prev_publish_time: 1717782832,
+///     ema_price: 16863708300,
+///     ema_conf: 16979099,
+/// });
+///
+/// let mut wire = [0u8; 85];
+/// assert_eq!(message.encode_wire(&mut wire), Ok(85));
+/// assert_eq!(Message::decode_wire(&wire), Ok(message));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Message<'a> {
+    PriceFeed(PriceFeedMessage),
+    Twap(TwapMessage),
+    PublisherStakeCaps(PublisherStakeCapsMessage<'a>),
+}
+
+impl<'a> Message<'a> {
+    pub const PRICE_FEED_TAG: u8 = 0;
+    pub const TWAP_TAG: u8 = 1;
+    pub const PUBLISHER_STAKE_CAPS_TAG: u8 = 2;
+
+    /// Decode a whole message. Fails with `UnknownMessageType` for an unknown message type,
+    /// `Truncated` if `v` is too short and `TrailingBytes` if bytes remain after the message.
+    pub fn decode_wire(v: &'a [u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader::new(v);
+        let message = match reader.u8()? {
+            Self::PRICE_FEED_TAG => Message::PriceFeed(PriceFeedMessage {
+                feed_id: *reader.array()?,
+                price: reader.i64()?,
+                conf: reader.u64()?,
+                exponent: reader.i32()?,
+                publish_time: reader.i64()?,
+                prev_publish_time: reader.i64()?,
+                ema_price: reader.i64()?,
+                ema_conf: reader.u64()?,
+            }),
+            Self::TWAP_TAG => Message::Twap(TwapMessage {
+                feed_id: *reader.array()?,
+                cumulative_price: reader.i128()?,
+                cumulative_conf: reader.u128()?,
+                num_down_slots: reader.u64()?,
+                exponent: reader.i32()?,
+                publish_time: reader.i64()?,
+                prev_publish_time: reader.i64()?,
+                publish_slot: reader.u64()?,
+            }),
+            Self::PUBLISHER_STAKE_CAPS_TAG => {
+                let publish_time = reader.i64()?;
+                let num_caps = reader.u16()?;
+                let caps =
+                    reader.take(usize::from(num_caps) * PublisherStakeCapsMessage::CAP_LEN)?;
+                Message::PublisherStakeCaps(PublisherStakeCapsMessage { publish_time, caps })
+            }
+            tag => return Err(ParseError::UnknownMessageType(tag)),
+        };
+        reader.finish()?;
+
+        Ok(message)
+    }
+
+    /// The encoded length of this message
+    pub fn wire_len(&self) -> usize {
+        1 + match self {
+            Message::PriceFeed(_) => PriceFeedMessage::LEN,
+            Message::Twap(_) => 32 + 16 + 16 + 8 + 4 + 8 + 8 + 8,
+            Message::PublisherStakeCaps(message) => 8 + 2 + message.caps.len(),
         }
     }
+
+    /// Encode this message at the start of `dst` and return the number of bytes written, i.e.
+    /// [`Message::wire_len`]. Fails with `Truncated` if `dst` is too short.
+    pub fn encode_wire(&self, dst: &mut [u8]) -> Result<usize, ParseError> {
+        let mut writer = Writer::new(dst);
+        match self {
+            Message::PriceFeed(message) => {
+                writer.put(&[Self::PRICE_FEED_TAG])?;
+                writer.put(&message.feed_id)?;
+                writer.put(&message.price.to_be_bytes())?;
+                writer.put(&message.conf.to_be_bytes())?;
+                writer.put(&message.exponent.to_be_bytes())?;
+                writer.put(&message.publish_time.to_be_bytes())?;
+                writer.put(&message.prev_publish_time.to_be_bytes())?;
+                writer.put(&message.ema_price.to_be_bytes())?;
+                writer.put(&message.ema_conf.to_be_bytes())?;
+            }
+            Message::Twap(message) => {
+                writer.put(&[Self::TWAP_TAG])?;
+                writer.put(&message.feed_id)?;
+                writer.put(&message.cumulative_price.to_be_bytes())?;
+                writer.put(&message.cumulative_conf.to_be_bytes())?;
+                writer.put(&message.num_down_slots.to_be_bytes())?;
+                writer.put(&message.exponent.to_be_bytes())?;
+                writer.put(&message.publish_time.to_be_bytes())?;
+                writer.put(&message.prev_publish_time.to_be_bytes())?;
+                writer.put(&message.publish_slot.to_be_bytes())?;
+            }
+            Message::PublisherStakeCaps(message) => {
+                // Decoded from at most u16::MAX caps, so the count fits
+                let num_caps = message.caps.len() / PublisherStakeCapsMessage::CAP_LEN;
+                writer.put(&[Self::PUBLISHER_STAKE_CAPS_TAG])?;
+                writer.put(&message.publish_time.to_be_bytes())?;
+                writer.put(&(num_caps as u16).to_be_bytes())?;
+                writer.put(message.caps)?;
+            }
+        }
+
+        Ok(writer.position())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Message<'a> {
+    type Error = ParseError;
+
+    fn try_from(v: &'a [u8]) -> Result<Self, Self::Error> {
+        Message::decode_wire(v)
+    }
 }
 
 #[cfg(test)]
@@ -113,4 +344,153 @@ mod tests {
         
         // dded 1e10 0000 0000 remains for the posted slot
     }
+
+    #[test]
+    fn price_feed_message_try_from_bytes_truncated() {
+        let bytes = [0u8; PriceFeedMessage::LEN - 1];
+        assert_eq!(
+            PriceFeedMessage::try_from(&bytes[..]),
+            Err(ParseError::Truncated {
+                expected: 84,
+                actual: 83
+            })
+        );
+    }
+
+    #[test]
+    fn price_feed_message_try_from_bytes_trailing() {
+        let bytes = [0u8; PriceFeedMessage::LEN + 4];
+        assert_eq!(
+            PriceFeedMessage::try_from(&bytes[..]),
+            Err(ParseError::TrailingBytes {
+                expected: 84,
+                actual: 88
+            })
+        );
+    }
+
+    #[test]
+    fn price_feed_message_round_trip() {
+        let hex_data = "22f123639d7ef4cd60314704340deddf371fd42472148f248e9d1a6d1a5eb2ac3acd8b7fd5d6b24301ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d107fc8e30300000049a7550100000000f8ffffff314963660000000030496366000000008cc427ed030000009b14030100000000dded1e100000000000";
+        let bytes = hex_to_bytes(hex_data);
+        let message = PriceFeedMessage::get_feed_from_bytes(&bytes[41..125]);
+
+        assert_eq!(&message.to_bytes()[..], &bytes[41..125]);
+    }
+
+    fn twap() -> TwapMessage {
+        TwapMessage {
+            feed_id: [3u8; 32],
+            cumulative_price: -(1 << 100),
+            cumulative_conf: 1 << 120,
+            num_down_slots: 12,
+            exponent: -8,
+            publish_time: 1717782833,
+            prev_publish_time: 1717782832,
+            publish_slot: 270_000_000,
+        }
+    }
+
+    #[test]
+    fn wire_price_feed_is_big_endian() {
+        let bytes = hex_to_bytes("22f123639d7ef4cd60314704340deddf371fd42472148f248e9d1a6d1a5eb2ac3acd8b7fd5d6b24301ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d107fc8e30300000049a7550100000000f8ffffff314963660000000030496366000000008cc427ed030000009b14030100000000dded1e100000000000");
+        let feed = PriceFeedMessage::get_feed_from_bytes(&bytes[41..125]);
+        let message = Message::PriceFeed(feed);
+
+        let mut wire = [0u8; 85];
+        assert_eq!(message.encode_wire(&mut wire), Ok(message.wire_len()));
+        assert_eq!(wire[0], Message::PRICE_FEED_TAG);
+        assert_eq!(&wire[1..33], &feed.feed_id);
+        // price, 16706469648
+        assert_eq!(&wire[33..41], &[0, 0, 0, 3, 0xe3, 0xc8, 0x7f, 0x10]);
+        // exponent, -8
+        assert_eq!(&wire[49..53], &[0xff, 0xff, 0xff, 0xf8]);
+        assert_eq!(Message::decode_wire(&wire), Ok(message));
+    }
+
+    #[test]
+    fn wire_twap_round_trip() {
+        let message = Message::Twap(twap());
+        let mut wire = [0u8; 110];
+        assert_eq!(message.encode_wire(&mut wire), Ok(101));
+        assert_eq!(wire[0], Message::TWAP_TAG);
+        assert_eq!(Message::try_from(&wire[..101]), Ok(message));
+    }
+
+    #[test]
+    fn wire_publisher_stake_caps() {
+        let mut wire = vec![Message::PUBLISHER_STAKE_CAPS_TAG];
+        wire.extend_from_slice(&1717782833i64.to_be_bytes());
+        wire.extend_from_slice(&2u16.to_be_bytes());
+        for (publisher, cap) in [([4u8; 32], 1_000u64), ([5u8; 32], u64::MAX)] {
+            wire.extend_from_slice(&publisher);
+            wire.extend_from_slice(&cap.to_be_bytes());
+        }
+
+        let Ok(Message::PublisherStakeCaps(message)) = Message::decode_wire(&wire) else {
+            panic!("not a publisher stake caps message");
+        };
+        assert_eq!(message.publish_time, 1717782833);
+        assert!(message.caps().eq([
+            PublisherStakeCap {
+                publisher: [4u8; 32],
+                cap: 1_000
+            },
+            PublisherStakeCap {
+                publisher: [5u8; 32],
+                cap: u64::MAX
+            },
+        ]));
+
+        let mut encoded = [0u8; 91];
+        let message = Message::PublisherStakeCaps(message);
+        assert_eq!(message.encode_wire(&mut encoded), Ok(wire.len()));
+        assert_eq!(&encoded[..], &wire[..]);
+    }
+
+    #[test]
+    fn wire_errors() {
+        let message = Message::Twap(twap());
+        let mut wire = [0u8; 102];
+        assert_eq!(
+            message.encode_wire(&mut wire[..100]),
+            Err(ParseError::Truncated {
+                expected: 101,
+                actual: 100
+            })
+        );
+        message.encode_wire(&mut wire).unwrap();
+
+        assert_eq!(
+            Message::decode_wire(&wire[..100]),
+            Err(ParseError::Truncated {
+                expected: 101,
+                actual: 100
+            })
+        );
+        assert_eq!(
+            Message::decode_wire(&wire),
+            Err(ParseError::TrailingBytes {
+                expected: 101,
+                actual: 102
+            })
+        );
+        wire[0] = 3;
+        assert_eq!(
+            Message::decode_wire(&wire[..101]),
+            Err(ParseError::UnknownMessageType(3))
+        );
+        assert_eq!(
+            Message::decode_wire(&[]),
+            Err(ParseError::Truncated {
+                expected: 1,
+                actual: 0
+            })
+        );
+        // Stake caps whose count runs past the end
+        assert!(matches!(
+            Message::decode_wire(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            Err(ParseError::Truncated { .. })
+        ));
+    }
 }
@@ -0,0 +1,251 @@
+use crate::{
+    error::{GetPriceError, Result},
+    messages::FeedId,
+    price_update::{Price, VerificationLevel},
+    source::PriceSource,
+};
+
+/// Every check a program wants to run before trusting a price, in one place. Declare it once as a
+/// `const` and pass it to every instruction that reads the same feed instead of keeping several
+/// getter arguments in sync by hand.
+///
+/// ```
+/// use pyth_min::{
+///     policy::PriceValidationPolicy,
+///     price_update::{get_feed_id_from_hex, VerificationLevel},
+/// };
+///
+/// const SOL_USD_POLICY: PriceValidationPolicy = PriceValidationPolicy::new(30)
+///     .with_feed_id(
+///         match get_feed_id_from_hex("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d") {
+///             Ok(id) => id,
+///             Err(_) => panic!("invalid feed id"),
+///         },
+///     )
+///     .with_verification_level(VerificationLevel::Full)
+///     .with_max_conf_ratio_bps(200)
+///     .with_max_future_skew(5);
+/// ```
+///
+/// Checks that are not configured are skipped, except that negative prices are rejected unless
+/// [`PriceValidationPolicy::allow_negative`] is set.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PriceValidationPolicy {
+    /// Maximum age of the price in seconds
+    pub maximum_age: u64,
+    /// Minimum verification level of the update
+    pub verification_level: VerificationLevel,
+    /// The feed the update must be for
+    pub feed_id: Option<FeedId>,
+    /// Maximum `conf / |price|`, in basis points
+    pub max_conf_ratio_bps: Option<u64>,
+    /// How many seconds the publish time may be ahead of the current time
+    pub max_future_skew: Option<u64>,
+    /// Whether negative prices are acceptable
+    pub allow_negative: bool,
+    /// Maximum `|price - ema_price| / |ema_price|`, in basis points
+    pub max_ema_divergence_bps: Option<u64>,
+    /// Maximum `|price - ema_price| / ema_conf`, in basis points
+    pub max_ema_divergence_conf_multiple_bps: Option<u64>,
+}
+
+impl PriceValidationPolicy {
+    /// A policy that only accepts fully verified, non-negative prices no older than
+    /// `maximum_age` seconds.
+    pub const fn new(maximum_age: u64) -> Self {
+        PriceValidationPolicy {
+            maximum_age,
+            verification_level: VerificationLevel::Full,
+            feed_id: None,
+            max_conf_ratio_bps: None,
+            max_future_skew: None,
+            allow_negative: false,
+            max_ema_divergence_bps: None,
+            max_ema_divergence_conf_multiple_bps: None,
+        }
+    }
+
+    /// Lowering the verification level from `Full` to `Partial` increases the risk of using a
+    /// malicious price update, see [`VerificationLevel`].
+    pub const fn with_verification_level(mut self, verification_level: VerificationLevel) -> Self {
+        self.verification_level = verification_level;
+        self
+    }
+
+    pub const fn with_feed_id(mut self, feed_id: FeedId) -> Self {
+        self.feed_id = Some(feed_id);
+        self
+    }
+
+    pub const fn with_max_conf_ratio_bps(mut self, max_conf_ratio_bps: u64) -> Self {
+        self.max_conf_ratio_bps = Some(max_conf_ratio_bps);
+        self
+    }
+
+    pub const fn with_max_future_skew(mut self, max_future_skew: u64) -> Self {
+        self.max_future_skew = Some(max_future_skew);
+        self
+    }
+
+    pub const fn allow_negative(mut self, allow_negative: bool) -> Self {
+        self.allow_negative = allow_negative;
+        self
+    }
+
+    pub const fn with_max_ema_divergence_bps(mut self, max_ema_divergence_bps: u64) -> Self {
+        self.max_ema_divergence_bps = Some(max_ema_divergence_bps);
+        self
+    }
+
+    /// Reject prices further from the EMA price than `max_conf_multiple_bps / 10_000` times the
+    /// EMA confidence.
+    pub const fn with_max_ema_divergence_conf_multiple_bps(
+        mut self,
+        max_conf_multiple_bps: u64,
+    ) -> Self {
+        self.max_ema_divergence_conf_multiple_bps = Some(max_conf_multiple_bps);
+        self
+    }
+
+    /// Run every configured check against `source` at unix time `now` and return the price if
+    /// they all pass. The error identifies the first check that failed, in this order:
+    /// verification level, feed id, age, future skew, sign, confidence ratio, EMA divergence.
+    pub fn validate<S: PriceSource + ?Sized>(&self, source: &S, now: i64) -> Result<Price> {
+        if !source.verification().gte(self.verification_level) {
+            return Err(GetPriceError::InsufficientVerificationLevel);
+        }
+        if let Some(feed_id) = &self.feed_id {
+            if source.feed_id() != *feed_id {
+                return Err(GetPriceError::MismatchedFeedId);
+            }
+        }
+        let price = source.price(now)?;
+        price.check_freshness(now, self.maximum_age, self.max_future_skew)?;
+        if !self.allow_negative && price.price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+        if let Some(max_conf_ratio_bps) = self.max_conf_ratio_bps {
+            price.check_conf_ratio(max_conf_ratio_bps)?;
+        }
+        if self.max_ema_divergence_bps.is_some()
+            || self.max_ema_divergence_conf_multiple_bps.is_some()
+        {
+            let ema_price = source.ema_price(now)?;
+            if let Some(max_divergence_bps) = self.max_ema_divergence_bps {
+                price.check_ema_divergence(&ema_price, max_divergence_bps)?;
+            }
+            if let Some(max_conf_multiple_bps) = self.max_ema_divergence_conf_multiple_bps {
+                price.check_ema_divergence_conf_multiple(&ema_price, max_conf_multiple_bps)?;
+            }
+        }
+
+        Ok(price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{MockPriceUpdate, SOL_USD};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn validate_accepts_good_price() {
+        let policy = PriceValidationPolicy::new(30)
+            .with_feed_id(SOL_USD)
+            .with_max_conf_ratio_bps(100)
+            .with_max_future_skew(5)
+            .with_max_ema_divergence_bps(500);
+        let price_update = MockPriceUpdate::new().age_seconds(NOW, 30).build();
+
+        assert_eq!(
+            policy.validate(&price_update, NOW),
+            price_update.get_price_unchecked(None)
+        );
+    }
+
+    #[test]
+    fn validate_reports_failed_check() {
+        let policy = PriceValidationPolicy::new(30)
+            .with_feed_id(SOL_USD)
+            .with_max_conf_ratio_bps(100)
+            .with_max_future_skew(5)
+            .with_max_ema_divergence_bps(500);
+        let fresh = MockPriceUpdate::new().age_seconds(NOW, 0);
+
+        assert_eq!(
+            policy.validate(&fresh.partial(13).build(), NOW),
+            Err(GetPriceError::InsufficientVerificationLevel)
+        );
+        assert_eq!(
+            policy.validate(&fresh.feed([1u8; 32]).build(), NOW),
+            Err(GetPriceError::MismatchedFeedId)
+        );
+        assert_eq!(
+            policy.validate(&fresh.age_seconds(NOW, 31).build(), NOW),
+            Err(GetPriceError::PriceTooOld)
+        );
+        assert_eq!(
+            policy.validate(&fresh.age_seconds(NOW, -6).build(), NOW),
+            Err(GetPriceError::PriceFromFuture)
+        );
+        assert!(policy
+            .validate(&fresh.age_seconds(NOW, -5).build(), NOW)
+            .is_ok());
+        assert_eq!(
+            policy.validate(&fresh.price(-100_00000000).build(), NOW),
+            Err(GetPriceError::NegativePrice)
+        );
+        assert_eq!(
+            policy.validate(&fresh.conf(1_00000001).build(), NOW),
+            Err(GetPriceError::ConfidenceTooWide {
+                conf_ratio_bps: 101
+            })
+        );
+        assert_eq!(
+            policy.validate(&fresh.price(106_00000000).build(), NOW),
+            Err(GetPriceError::EmaDivergenceTooLarge {
+                divergence_bps: 600
+            })
+        );
+    }
+
+    #[test]
+    fn validate_ema_divergence_in_conf_multiples() {
+        // 3x the EMA confidence of 0.05
+        let policy =
+            PriceValidationPolicy::new(30).with_max_ema_divergence_conf_multiple_bps(30_000);
+        let fresh = MockPriceUpdate::new().age_seconds(NOW, 0);
+
+        assert!(policy
+            .validate(&fresh.price(100_15000000).build(), NOW)
+            .is_ok());
+        assert!(policy
+            .validate(&fresh.price(99_85000000).build(), NOW)
+            .is_ok());
+        assert_eq!(
+            policy.validate(&fresh.price(100_15000001).build(), NOW),
+            Err(GetPriceError::EmaDivergenceOutsideConfidence {
+                conf_multiple_bps: 30_001
+            })
+        );
+    }
+
+    #[test]
+    fn validate_skips_unset_checks() {
+        let policy = PriceValidationPolicy::new(30)
+            .with_verification_level(VerificationLevel::Partial { num_signatures: 5 })
+            .allow_negative(true);
+        let price_update = MockPriceUpdate::new()
+            .feed([1u8; 32])
+            .price(-100_00000000)
+            .conf(u64::MAX)
+            .partial(5)
+            .age_seconds(NOW, -1_000)
+            .build();
+
+        assert!(policy.validate(&price_update, NOW).is_ok());
+    }
+}
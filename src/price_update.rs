@@ -1,9 +1,56 @@
 use crate::{
-    byte_utils::{interpret_bytes_as_u64, PubkeyBytes},
-    error::GetPriceError,
+    byte_utils::{hex_digit, PubkeyBytes, DISCRIMINATOR_AS_BYTES},
+    error::{GetPriceError, ParseError},
+    math::{div_round, Rounding, BPS_DENOMINATOR},
     messages::{FeedId, PriceFeedMessage},
+    price_update_ref::PriceUpdateV2Ref,
+    pythnet::PYTH_FEED_OWNER,
 };
 
+/// Parse a feed id from its hex representation, as listed on
+/// <https://pyth.network/developers/price-feed-ids>. Accepts 64 hex characters, upper or lower case,
+/// with or without a `0x` prefix.
+///
+/// This is a `const fn`, so feed ids can be declared at compile time:
+///
+/// ```
+/// use pyth_min::{messages::FeedId, price_update::get_feed_id_from_hex};
+///
+/// const SOL_USD: FeedId =
+///     match get_feed_id_from_hex("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d") {
+///         Ok(id) => id,
+///         Err(_) => panic!("invalid feed id"),
+///     };
+/// assert_eq!(SOL_USD[0], 0xef);
+/// ```
+pub const fn get_feed_id_from_hex(input: &str) -> Result<FeedId, GetPriceError> {
+    let bytes = input.as_bytes();
+    let start = if bytes.len() >= 2 && bytes[0] == b'0' && (bytes[1] == b'x' || bytes[1] == b'X') {
+        2
+    } else {
+        0
+    };
+    if bytes.len() - start != 64 {
+        return Err(GetPriceError::FeedIdMustBe32Bytes);
+    }
+
+    let mut feed_id = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        let high = match hex_digit(bytes[start + 2 * i]) {
+            Some(d) => d,
+            None => return Err(GetPriceError::FeedIdNonHexCharacter),
+        };
+        let low = match hex_digit(bytes[start + 2 * i + 1]) {
+            Some(d) => d,
+            None => return Err(GetPriceError::FeedIdNonHexCharacter),
+        };
+        feed_id[i] = (high << 4) | low;
+        i += 1;
+    }
+    Ok(feed_id)
+}
+
 /// Pyth price updates are bridged to all blockchains via Wormhole.
 /// Using the price updates on another chain requires verifying the signatures of the Wormhole guardians.
 /// The usual process is to check the signatures for two thirds of the total number of guardians, but this can be cumbersome on Solana because of the transaction size limits,
@@ -42,16 +89,76 @@ impl VerificationLevel {
     /// * 0x01 for `Full`, with no additional bytes required.
     ///
     /// If the VerificationLevel level is Full, this will be one byte. If Partial, two bytes.
+    ///
+    /// # Panics
+    /// On an unknown tag or a slice of the wrong length. Use
+    /// [`VerificationLevel::try_get_verification_from_bytes`] to handle these as errors instead.
     pub fn get_verification_from_bytes(v: &[u8]) -> VerificationLevel {
-        assert!(v.len() == 1 || v.len() == 2);
-        match v.get(0).unwrap() {
+        Self::try_get_verification_from_bytes(v).expect("invalid VerificationLevel bytes")
+    }
+
+    /// Fallible version of [`VerificationLevel::get_verification_from_bytes`]. The slice must be
+    /// exactly as long as the level it encodes: one byte for `Full`, two bytes for `Partial`.
+    pub fn try_get_verification_from_bytes(v: &[u8]) -> Result<VerificationLevel, ParseError> {
+        let tag = *v.first().ok_or(ParseError::Truncated {
+            expected: 1,
+            actual: 0,
+        })?;
+        let expected = Self::encoded_len_from_tag(tag)?;
+        if v.len() < expected {
+            return Err(ParseError::Truncated {
+                expected,
+                actual: v.len(),
+            });
+        }
+        if v.len() > expected {
+            return Err(ParseError::TrailingBytes {
+                expected,
+                actual: v.len(),
+            });
+        }
+
+        Ok(match tag {
             0x01 => VerificationLevel::Full,
-            0x00 => {
-                let num_signatures = *v.get(1).unwrap();
-                VerificationLevel::Partial { num_signatures }
-            }
-            _ => panic!("invalid enum discrim"),
+            _ => VerificationLevel::Partial {
+                num_signatures: v[1],
+            },
+        })
+    }
+
+    /// The number of bytes this `VerificationLevel` occupies when serialized.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            VerificationLevel::Full => 1,
+            VerificationLevel::Partial { .. } => 2,
+        }
+    }
+
+    /// Serialize into the layout read by [`VerificationLevel::get_verification_from_bytes`],
+    /// using `dst` as scratch space. Returns the [`VerificationLevel::encoded_len`] bytes written.
+    pub fn write<'b>(&self, dst: &'b mut [u8; 2]) -> &'b [u8] {
+        match self {
+            VerificationLevel::Full => dst[0] = 0x01,
+            VerificationLevel::Partial { num_signatures } => *dst = [0x00, *num_signatures],
         }
+        &dst[..self.encoded_len()]
+    }
+
+    /// The number of bytes a `VerificationLevel` with the given leading tag byte occupies.
+    pub fn encoded_len_from_tag(tag: u8) -> Result<usize, ParseError> {
+        match tag {
+            0x01 => Ok(1),
+            0x00 => Ok(2),
+            _ => Err(ParseError::UnknownVerificationTag(tag)),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for VerificationLevel {
+    type Error = ParseError;
+
+    fn try_from(v: &[u8]) -> Result<Self, Self::Error> {
+        VerificationLevel::try_get_verification_from_bytes(v)
     }
 }
 
@@ -83,6 +190,109 @@ pub struct EmaPrice {
     pub publish_time: i64,
 }
 
+/// `conf / |price|` in basis points, rounded up. A zero price has an infinite ratio, reported as
+/// `u64::MAX`.
+fn conf_ratio_bps(price: i64, conf: u64) -> u64 {
+    if price == 0 {
+        return u64::MAX;
+    }
+    let ratio = div_round(
+        i128::from(conf) * i128::from(BPS_DENOMINATOR),
+        i128::from(price.unsigned_abs()),
+        Rounding::Ceil,
+    );
+    u64::try_from(ratio).unwrap_or(u64::MAX)
+}
+
+fn check_conf_ratio(price: i64, conf: u64, max_conf_ratio_bps: u64) -> Result<(), GetPriceError> {
+    let conf_ratio_bps = conf_ratio_bps(price, conf);
+    if conf_ratio_bps > max_conf_ratio_bps {
+        return Err(GetPriceError::ConfidenceTooWide { conf_ratio_bps });
+    }
+    Ok(())
+}
+
+/// Fails with `PriceTooOld` if `publish_time` is more than `maximum_age` seconds before `now`, and
+/// with `PriceFromFuture` if it is more than `max_future_skew` seconds after it. Computed in 128
+/// bits, so extreme inputs cannot overflow.
+fn check_freshness(
+    publish_time: i64,
+    now: i64,
+    maximum_age: u64,
+    max_future_skew: Option<u64>,
+) -> Result<(), GetPriceError> {
+    let age = i128::from(now) - i128::from(publish_time);
+    if age > i128::from(maximum_age) {
+        return Err(GetPriceError::PriceTooOld);
+    }
+    if let Some(max_future_skew) = max_future_skew {
+        if -age > i128::from(max_future_skew) {
+            return Err(GetPriceError::PriceFromFuture);
+        }
+    }
+    Ok(())
+}
+
+impl Price {
+    /// Seconds elapsed between `publish_time` and `now`, negative if the price was published after
+    /// `now`. Saturates at the bounds of an i64.
+    pub fn age_seconds(&self, now: i64) -> i64 {
+        now.saturating_sub(self.publish_time)
+    }
+
+    /// Fails with `PriceTooOld` if the price is older than `maximum_age` seconds at unix time
+    /// `now`, and with `PriceFromFuture` if it was published more than `max_future_skew` seconds
+    /// after `now`. Pass `None` to accept any publish time in the future.
+    pub fn check_freshness(
+        &self,
+        now: i64,
+        maximum_age: u64,
+        max_future_skew: Option<u64>,
+    ) -> Result<(), GetPriceError> {
+        check_freshness(self.publish_time, now, maximum_age, max_future_skew)
+    }
+
+    /// The width of the confidence interval relative to the price, `conf / |price|`, in basis
+    /// points and rounded up. A zero price reports `u64::MAX`.
+    pub fn conf_ratio_bps(&self) -> u64 {
+        conf_ratio_bps(self.price, self.conf)
+    }
+
+    /// Fails with `ConfidenceTooWide` if `conf / |price|` exceeds `max_conf_ratio_bps`.
+    pub fn check_conf_ratio(&self, max_conf_ratio_bps: u64) -> Result<(), GetPriceError> {
+        check_conf_ratio(self.price, self.conf, max_conf_ratio_bps)
+    }
+}
+
+impl EmaPrice {
+    /// Seconds elapsed between `publish_time` and `now`, negative if the price was published after
+    /// `now`. Saturates at the bounds of an i64.
+    pub fn age_seconds(&self, now: i64) -> i64 {
+        now.saturating_sub(self.publish_time)
+    }
+
+    /// Same as [`Price::check_freshness`].
+    pub fn check_freshness(
+        &self,
+        now: i64,
+        maximum_age: u64,
+        max_future_skew: Option<u64>,
+    ) -> Result<(), GetPriceError> {
+        check_freshness(self.publish_time, now, maximum_age, max_future_skew)
+    }
+
+    /// The width of the EMA confidence interval relative to the EMA price, in basis points and
+    /// rounded up. A zero price reports `u64::MAX`.
+    pub fn conf_ratio_bps(&self) -> u64 {
+        conf_ratio_bps(self.ema_price, self.ema_conf)
+    }
+
+    /// Fails with `ConfidenceTooWide` if `ema_conf / |ema_price|` exceeds `max_conf_ratio_bps`.
+    pub fn check_conf_ratio(&self, max_conf_ratio_bps: u64) -> Result<(), GetPriceError> {
+        check_conf_ratio(self.ema_price, self.ema_conf, max_conf_ratio_bps)
+    }
+}
+
 impl PriceUpdateV2 {
     pub const LEN: usize = 8 + 32 + 2 + 32 + 8 + 8 + 4 + 8 + 8 + 8 + 8 + 8;
 
@@ -95,41 +305,82 @@ impl PriceUpdateV2 {
     /// Skip the first 8 bytes (Anchor discriminator)
     ///
     /// `let message_bytes = &data[8..];`
+    ///
+    /// # Panics
+    /// If the bytes are not a valid PriceUpdateV2. Use
+    /// [`PriceUpdateV2::try_get_price_update_v2_from_bytes`] to handle this as an error instead.
     pub fn get_price_update_v2_from_bytes(v: &[u8]) -> PriceUpdateV2 {
-        // assert!(v.len() == PriceUpdateV2::LEN);
+        Self::try_get_price_update_v2_from_bytes(v).expect("invalid PriceUpdateV2 bytes")
+    }
 
-        let write_authority: PubkeyBytes = {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&v[0..32]);
-            arr
-        };
-        // If VerificationLevel::Full (0x01) then only one byte is used, otherwise 2 bytes.
-        let verification_one_byte = v[32] == 0x01;
+    /// Fallible version of [`PriceUpdateV2::get_price_update_v2_from_bytes`]. Expects the account
+    /// data without the 8-byte discriminator. Any bytes after the posted slot are padding and are
+    /// accepted as long as the total does not exceed `LEN - 8`.
+    ///
+    /// See [`PriceUpdateV2Ref`] to read fields without copying the whole account.
+    pub fn try_get_price_update_v2_from_bytes(v: &[u8]) -> Result<PriceUpdateV2, ParseError> {
+        PriceUpdateV2Ref::try_from_bytes(v).map(|view| view.to_price_update())
+    }
 
-        let verification_level = if verification_one_byte {
-            VerificationLevel::get_verification_from_bytes(&v[32..33])
-        } else {
-            VerificationLevel::get_verification_from_bytes(&v[32..34])
-        };
+    /// Interpret a PriceUpdateV2 from the full account data, including the 8-byte discriminator.
+    /// This is the recommended entry point for on-chain programs, as it checks everything needed
+    /// to trust that the account really is a Pyth price update:
+    ///
+    /// * the account is owned by [`PYTH_FEED_OWNER`],
+    /// * the data is exactly [`PriceUpdateV2::LEN`] bytes,
+    /// * the data starts with [`DISCRIMINATOR_AS_BYTES`].
+    ///
+    /// `let price_v2 = PriceUpdateV2::try_from_account_data(&ctx.accounts.price.try_borrow_data()?, &ctx.accounts.price.owner.to_bytes())?;`
+    pub fn try_from_account_data(
+        data: &[u8],
+        owner: &PubkeyBytes,
+    ) -> Result<PriceUpdateV2, ParseError> {
+        Self::try_from_account_data_with_owners(data, owner, &[PYTH_FEED_OWNER])
+    }
 
-        let price_message = if verification_one_byte {
-            PriceFeedMessage::get_feed_from_bytes(&v[33..117])
-        } else {
-            PriceFeedMessage::get_feed_from_bytes(&v[34..118])
-        };
+    /// Same as [`PriceUpdateV2::try_from_account_data`], but accepts any owner in `allowed_owners`
+    /// instead of only [`PYTH_FEED_OWNER`]. Useful if you post price updates with a fork of the
+    /// receiver program.
+    pub fn try_from_account_data_with_owners(
+        data: &[u8],
+        owner: &PubkeyBytes,
+        allowed_owners: &[PubkeyBytes],
+    ) -> Result<PriceUpdateV2, ParseError> {
+        PriceUpdateV2Ref::try_from_account_data_with_owners(data, owner, allowed_owners)
+            .map(|view| view.to_price_update())
+    }
 
-        let posted_slot = if verification_one_byte {
-            interpret_bytes_as_u64(&v[117..125])
-        } else {
-            interpret_bytes_as_u64(&v[118..126])
-        };
+    /// Serialize into the exact on-chain account layout: discriminator, write authority,
+    /// verification level (1 or 2 bytes), price message, posted slot, and zero padding up to
+    /// [`PriceUpdateV2::LEN`]. Round-trips with [`PriceUpdateV2::try_from_account_data`], which
+    /// makes it easy to fabricate oracle accounts in local program tests.
+    pub fn to_account_bytes(&self) -> [u8; PriceUpdateV2::LEN] {
+        let mut scratch = [0u8; 2];
+        let verification = self.verification_level.write(&mut scratch);
+        let message_start = 40 + verification.len();
+        let slot_start = message_start + PriceFeedMessage::LEN;
+
+        let mut data = [0u8; PriceUpdateV2::LEN];
+        data[..8].copy_from_slice(&DISCRIMINATOR_AS_BYTES);
+        data[8..40].copy_from_slice(&self.write_authority);
+        data[40..message_start].copy_from_slice(verification);
+        data[message_start..slot_start].copy_from_slice(&self.price_message.to_bytes());
+        data[slot_start..slot_start + 8].copy_from_slice(&self.posted_slot.to_le_bytes());
+        data
+    }
 
-        PriceUpdateV2 {
-            write_authority,
-            verification_level,
-            price_message,
-            posted_slot,
-        }
+    /// Same as [`PriceUpdateV2::to_account_bytes`], but writes into an existing buffer (e.g. the
+    /// data of an account you are creating, via `data.try_into()`).
+    pub fn write_to(&self, dst: &mut [u8; PriceUpdateV2::LEN]) {
+        *dst = self.to_account_bytes();
+    }
+}
+
+impl TryFrom<&[u8]> for PriceUpdateV2 {
+    type Error = ParseError;
+
+    fn try_from(v: &[u8]) -> Result<Self, Self::Error> {
+        PriceUpdateV2::try_get_price_update_v2_from_bytes(v)
     }
 }
 
@@ -143,8 +394,8 @@ impl PriceUpdateV2 {
     ///
     /// It is therefore unsafe to use this function without any extra checks, as it allows for the possibility of using unverified or outdated price updates.
     pub fn get_price_unchecked(&self, feed_id: Option<&FeedId>) -> Result<Price, GetPriceError> {
-        if feed_id.is_some() {
-            if self.price_message.feed_id != *feed_id.unwrap() {
+        if let Some(feed_id) = feed_id {
+            if self.price_message.feed_id != *feed_id {
                 return Err(GetPriceError::MismatchedFeedId);
             }
         }
@@ -158,8 +409,8 @@ impl PriceUpdateV2 {
     }
 
     pub fn get_ema_price_unchecked(&self, feed_id: Option<&FeedId>) -> Result<EmaPrice, GetPriceError> {
-        if feed_id.is_some() {
-            if self.price_message.feed_id != *feed_id.unwrap() {
+        if let Some(feed_id) = feed_id {
+            if self.price_message.feed_id != *feed_id {
                 return Err(GetPriceError::MismatchedFeedId);
             }
         }
@@ -182,24 +433,19 @@ impl PriceUpdateV2 {
     ///
     /// # Example
     /// ```
-    /// use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, VerificationLevel, PriceUpdateV2};
-    /// use anchor_lang::prelude::*;
+    /// use pyth_min::error::GetPriceError;
+    /// use pyth_min::messages::FeedId;
+    /// use pyth_min::price_update::{PriceUpdateV2, VerificationLevel};
     ///
-    /// const MAXIMUM_AGE : u64 = 30;
-    /// const FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"; // SOL/USD
+    /// const MAXIMUM_AGE: u64 = 30;
     ///
-    /// #[derive(Accounts)]
-    /// #[instruction(amount_in_usd : u64)]
-    /// pub struct ReadPriceAccount<'info> {
-    ///     pub price_update: Account<'info, PriceUpdateV2>,
+    /// // In an Anchor program, `price_update` is an `Account<'info, PriceUpdateV2>` and
+    /// // `unix_timestamp` is `Clock::get()?.unix_timestamp`.
+    /// fn read_price(price_update: &PriceUpdateV2, unix_timestamp: i64, feed_id: &FeedId) -> Result<i64, GetPriceError> {
+    ///     let price = price_update.get_price_no_older_than_with_custom_verification_level(unix_timestamp, MAXIMUM_AGE, Some(feed_id), VerificationLevel::Partial { num_signatures: 5 })?;
+    ///     Ok(price.price)
     /// }
-    ///
-    /// pub fn read_price_account(ctx : Context<ReadPriceAccount>) -> Result<()> {
-    ///     let price_update = &mut ctx.accounts.price_update;
-    ///     let price = price_update.get_price_no_older_than_with_custom_verification_level(&Clock::get()?.unix_timestamp, MAXIMUM_AGE, &get_feed_id_from_hex(FEED_ID)?, VerificationLevel::Partial{num_signatures: 5})?;
-    ///     Ok(())
-    /// }
-    ///```
+    /// ```
     pub fn get_price_no_older_than_with_custom_verification_level(
         &self,
         unix_timestamp: i64,
@@ -212,13 +458,7 @@ impl PriceUpdateV2 {
         }
 
         let price = self.get_price_unchecked(feed_id)?;
-        if !(price
-            .publish_time
-            .saturating_add(maximum_age.try_into().unwrap())
-            >= unix_timestamp)
-        {
-            return Err(GetPriceError::PriceTooOld);
-        }
+        price.check_freshness(unix_timestamp, maximum_age, None)?;
 
         Ok(price)
     }
@@ -235,13 +475,167 @@ impl PriceUpdateV2 {
         }
 
         let ema_price = self.get_ema_price_unchecked(feed_id)?;
-        if !(ema_price
-            .publish_time
-            .saturating_add(maximum_age.try_into().unwrap())
-            >= unix_timestamp)
-        {
-            return Err(GetPriceError::PriceTooOld);
+        ema_price.check_freshness(unix_timestamp, maximum_age, None)?;
+
+        Ok(ema_price)
+    }
+
+    /// Same as [`PriceUpdateV2::get_price_no_older_than_with_custom_verification_level`], but
+    /// also rejects prices published more than `max_future_skew` seconds after `unix_timestamp`
+    /// with `PriceFromFuture`. A publish time ahead of the cluster clock is expected by a few
+    /// seconds at most, anything further means the clock or the update cannot be trusted.
+    pub fn get_price_no_older_than_with_max_future_skew(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        max_future_skew: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<Price, GetPriceError> {
+        let price = self.get_price_with_custom_verification_level(feed_id, verification_level)?;
+        price.check_freshness(unix_timestamp, maximum_age, Some(max_future_skew))?;
+
+        Ok(price)
+    }
+
+    pub fn get_ema_price_no_older_than_with_max_future_skew(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        max_future_skew: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<EmaPrice, GetPriceError> {
+        let ema_price =
+            self.get_ema_price_with_custom_verification_level(feed_id, verification_level)?;
+        ema_price.check_freshness(unix_timestamp, maximum_age, Some(max_future_skew))?;
+
+        Ok(ema_price)
+    }
+
+    /// How many slots ago the update was posted, zero if `posted_slot` is ahead of `current_slot`.
+    pub fn posted_slot_age(&self, current_slot: u64) -> u64 {
+        current_slot.saturating_sub(self.posted_slot)
+    }
+
+    fn check_posted_slot(&self, current_slot: u64, max_slots: u64) -> Result<(), GetPriceError> {
+        if self.posted_slot_age(current_slot) > max_slots {
+            return Err(GetPriceError::PostedSlotTooOld);
         }
+        Ok(())
+    }
+
+    /// Get a `Price` from an update posted no more than `max_slots` slots before `current_slot`
+    /// (e.g. `Clock::get()?.slot`), regardless of its `publish_time`. Fails with
+    /// `PostedSlotTooOld` otherwise.
+    pub fn get_price_no_older_than_slots(
+        &self,
+        current_slot: u64,
+        max_slots: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<Price, GetPriceError> {
+        let price = self.get_price_with_custom_verification_level(feed_id, verification_level)?;
+        self.check_posted_slot(current_slot, max_slots)?;
+
+        Ok(price)
+    }
+
+    pub fn get_ema_price_no_older_than_slots(
+        &self,
+        current_slot: u64,
+        max_slots: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<EmaPrice, GetPriceError> {
+        let ema_price =
+            self.get_ema_price_with_custom_verification_level(feed_id, verification_level)?;
+        self.check_posted_slot(current_slot, max_slots)?;
+
+        Ok(ema_price)
+    }
+
+    /// Both [`PriceUpdateV2::get_price_no_older_than_with_custom_verification_level`] and
+    /// [`PriceUpdateV2::get_price_no_older_than_slots`]: the price must be no older than
+    /// `maximum_age` seconds and posted no more than `max_slots` slots ago. Time staleness is
+    /// reported first.
+    pub fn get_price_no_older_than_with_slots(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        current_slot: u64,
+        max_slots: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<Price, GetPriceError> {
+        let price = self.get_price_no_older_than_with_custom_verification_level(
+            unix_timestamp,
+            maximum_age,
+            feed_id,
+            verification_level,
+        )?;
+        self.check_posted_slot(current_slot, max_slots)?;
+
+        Ok(price)
+    }
+
+    pub fn get_ema_price_no_older_than_with_slots(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        current_slot: u64,
+        max_slots: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+    ) -> Result<EmaPrice, GetPriceError> {
+        let ema_price = self.get_ema_price_no_older_than_with_custom_verification_level(
+            unix_timestamp,
+            maximum_age,
+            feed_id,
+            verification_level,
+        )?;
+        self.check_posted_slot(current_slot, max_slots)?;
+
+        Ok(ema_price)
+    }
+
+    /// Same as [`PriceUpdateV2::get_price_no_older_than_with_custom_verification_level`], but
+    /// also rejects prices whose confidence interval is wider than `max_conf_ratio_bps` basis
+    /// points of the price, e.g. `200` to require `conf <= 2% * |price|`.
+    pub fn get_price_no_older_than_with_max_conf_ratio(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+        max_conf_ratio_bps: u64,
+    ) -> Result<Price, GetPriceError> {
+        let price = self.get_price_no_older_than_with_custom_verification_level(
+            unix_timestamp,
+            maximum_age,
+            feed_id,
+            verification_level,
+        )?;
+        price.check_conf_ratio(max_conf_ratio_bps)?;
+
+        Ok(price)
+    }
+
+    pub fn get_ema_price_no_older_than_with_max_conf_ratio(
+        &self,
+        unix_timestamp: i64,
+        maximum_age: u64,
+        feed_id: Option<&FeedId>,
+        verification_level: VerificationLevel,
+        max_conf_ratio_bps: u64,
+    ) -> Result<EmaPrice, GetPriceError> {
+        let ema_price = self.get_ema_price_no_older_than_with_custom_verification_level(
+            unix_timestamp,
+            maximum_age,
+            feed_id,
+            verification_level,
+        )?;
+        ema_price.check_conf_ratio(max_conf_ratio_bps)?;
 
         Ok(ema_price)
     }
@@ -274,30 +668,25 @@ impl PriceUpdateV2 {
     ///
     /// # Example
     /// ```
-    /// use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-    /// use anchor_lang::prelude::*;
+    /// use pyth_min::error::GetPriceError;
+    /// use pyth_min::messages::FeedId;
+    /// use pyth_min::price_update::PriceUpdateV2;
     ///
-    /// const MAXIMUM_AGE : u64 = 30;
-    /// const FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"; // SOL/USD
+    /// const MAXIMUM_AGE: u64 = 30;
     ///
-    /// #[derive(Accounts)]
-    /// #[instruction(amount_in_usd : u64)]
-    /// pub struct ReadPriceAccount<'info> {
-    ///     pub price_update: Account<'info, PriceUpdateV2>,
+    /// // In an Anchor program, `price_update` is an `Account<'info, PriceUpdateV2>` and
+    /// // `unix_timestamp` is `Clock::get()?.unix_timestamp`.
+    /// fn read_price(price_update: &PriceUpdateV2, unix_timestamp: i64, feed_id: &FeedId) -> Result<i64, GetPriceError> {
+    ///     let price = price_update.get_price_no_older_than(unix_timestamp, MAXIMUM_AGE, Some(feed_id))?;
+    ///     Ok(price.price)
     /// }
-    ///
-    /// pub fn read_price_account(ctx : Context<ReadPriceAccount>) -> Result<()> {
-    ///     let price_update = &mut ctx.accounts.price_update;
-    ///     let price = price_update.get_price_no_older_than(&Clock::get()?.unix_timestamp, MAXIMUM_AGE, &get_feed_id_from_hex(FEED_ID)?)?;
-    ///     Ok(())
-    /// }
-    ///```
+    /// ```
     pub fn get_price_no_older_than(
         &self,
         unix_timestamp: i64,
         maximum_age: u64,
         feed_id: Option<&FeedId>,
-    ) -> std::result::Result<Price, GetPriceError> {
+    ) -> core::result::Result<Price, GetPriceError> {
         self.get_price_no_older_than_with_custom_verification_level(
             unix_timestamp,
             maximum_age,
@@ -311,7 +700,7 @@ impl PriceUpdateV2 {
         unix_timestamp: i64,
         maximum_age: u64,
         feed_id: Option<&FeedId>,
-    ) -> std::result::Result<EmaPrice, GetPriceError> {
+    ) -> core::result::Result<EmaPrice, GetPriceError> {
         self.get_ema_price_no_older_than_with_custom_verification_level(
             unix_timestamp,
             maximum_age,
@@ -323,7 +712,10 @@ impl PriceUpdateV2 {
 
 #[cfg(test)]
 mod tests {
-    use crate::byte_utils::hex_to_bytes;
+    use crate::{
+        byte_utils::hex_to_bytes,
+        testing::{MockPriceUpdate, SOL_USD},
+    };
 
     use super::*;
 
@@ -484,7 +876,7 @@ mod tests {
             required_verification_level,
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
         assert_eq!(result.unwrap().price, 1000);
     }
 
@@ -526,7 +918,7 @@ mod tests {
         );
 
         // Assert that the result is an error and it's `PriceTooOld`
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         assert_eq!(result.unwrap_err(), GetPriceError::PriceTooOld);
     }
 
@@ -566,7 +958,416 @@ mod tests {
             required_verification_level,
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
         assert_eq!(result.unwrap().price, 1000);
     }
+
+    #[test]
+    fn verification_try_from_bytes_errors() {
+        assert_eq!(
+            VerificationLevel::try_from(&[][..]),
+            Err(ParseError::Truncated {
+                expected: 1,
+                actual: 0
+            })
+        );
+        assert_eq!(
+            VerificationLevel::try_from(&[0x00][..]),
+            Err(ParseError::Truncated {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            VerificationLevel::try_from(&[0x01, 0x05][..]),
+            Err(ParseError::TrailingBytes {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            VerificationLevel::try_from(&[0x02][..]),
+            Err(ParseError::UnknownVerificationTag(0x02))
+        );
+    }
+
+    #[test]
+    fn pricev2_try_from_bytes_errors() {
+        // From mainnet: https://solana.fm/address/7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE
+        let hex_data = "22f123639d7ef4cd60314704340deddf371fd42472148f248e9d1a6d1a5eb2ac3acd8b7fd5d6b24301ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d107fc8e30300000049a7550100000000f8ffffff314963660000000030496366000000008cc427ed030000009b14030100000000dded1e100000000000";
+        let mut bytes = hex_to_bytes(hex_data);
+
+        // The trailing padding byte is optional
+        assert!(PriceUpdateV2::try_from(&bytes[8..133]).is_ok());
+        assert_eq!(
+            PriceUpdateV2::try_from(&bytes[8..100]),
+            Err(ParseError::Truncated {
+                expected: 125,
+                actual: 92
+            })
+        );
+        assert_eq!(
+            PriceUpdateV2::try_from(&bytes[8..20]),
+            Err(ParseError::Truncated {
+                expected: 33,
+                actual: 12
+            })
+        );
+
+        bytes.push(0);
+        assert_eq!(
+            PriceUpdateV2::try_from(&bytes[8..]),
+            Err(ParseError::TrailingBytes {
+                expected: 126,
+                actual: 127
+            })
+        );
+
+        bytes[40] = 0x07;
+        assert_eq!(
+            PriceUpdateV2::try_from(&bytes[8..134]),
+            Err(ParseError::UnknownVerificationTag(0x07))
+        );
+    }
+
+    #[test]
+    fn feed_id_from_hex() {
+        let expected: FeedId = [
+            239, 13, 139, 111, 218, 44, 235, 164, 29, 161, 93, 64, 149, 209, 218, 57, 42, 13, 47,
+            142, 208, 198, 199, 188, 15, 76, 250, 200, 194, 128, 181, 109,
+        ];
+        let hex = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+        assert_eq!(get_feed_id_from_hex(hex), Ok(expected));
+        assert_eq!(get_feed_id_from_hex(&format!("0x{}", hex)), Ok(expected));
+        assert_eq!(
+            get_feed_id_from_hex(&format!("0X{}", hex.to_uppercase())),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn feed_id_from_hex_errors() {
+        assert_eq!(
+            get_feed_id_from_hex("0xef0d"),
+            Err(GetPriceError::FeedIdMustBe32Bytes)
+        );
+        assert_eq!(get_feed_id_from_hex(""), Err(GetPriceError::FeedIdMustBe32Bytes));
+        assert_eq!(
+            get_feed_id_from_hex("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56"),
+            Err(GetPriceError::FeedIdMustBe32Bytes)
+        );
+        assert_eq!(
+            get_feed_id_from_hex("0xzz0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"),
+            Err(GetPriceError::FeedIdNonHexCharacter)
+        );
+    }
+
+    #[test]
+    fn pricev2_from_account_data() {
+        // From devnet: https://solana.fm/address/DMzo13MxzhrU1dbtJRCxdLoa9zwWowBJu17KhRQ5tLWM
+        let hex_data =  "22f123639d7ef4cd0d881b9f67c8cb3d52fd2eb27d13c20951d199212b75021d55ecbf5e183b8cdb0005ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d5eaf3497030000000e62e80000000000f8fffffffb4e686600000000fa4e686600000000f45b539503000000ae73de000000000011ce2d1200000000";
+        let mut bytes = hex_to_bytes(hex_data);
+
+        let message = PriceUpdateV2::try_from_account_data(&bytes, &PYTH_FEED_OWNER).unwrap();
+        assert_eq!(message, PriceUpdateV2::get_price_update_v2_from_bytes(&bytes[8..]));
+
+        let other_owner = [7u8; 32];
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data(&bytes, &other_owner),
+            Err(ParseError::InvalidOwner)
+        );
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data_with_owners(&bytes, &other_owner, &[other_owner]),
+            Ok(message)
+        );
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data(&bytes[..133], &PYTH_FEED_OWNER),
+            Err(ParseError::Truncated {
+                expected: 134,
+                actual: 133
+            })
+        );
+
+        bytes[0] = 0x23;
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data(&bytes, &PYTH_FEED_OWNER),
+            Err(ParseError::InvalidDiscriminator)
+        );
+    }
+
+    #[test]
+    fn pricev2_to_account_bytes_round_trip() {
+        // From mainnet: https://solana.fm/address/7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE
+        let full = hex_to_bytes("22f123639d7ef4cd60314704340deddf371fd42472148f248e9d1a6d1a5eb2ac3acd8b7fd5d6b24301ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d107fc8e30300000049a7550100000000f8ffffff314963660000000030496366000000008cc427ed030000009b14030100000000dded1e100000000000");
+        // From devnet: https://solana.fm/address/DMzo13MxzhrU1dbtJRCxdLoa9zwWowBJu17KhRQ5tLWM
+        let partial = hex_to_bytes("22f123639d7ef4cd0d881b9f67c8cb3d52fd2eb27d13c20951d199212b75021d55ecbf5e183b8cdb0005ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d5eaf3497030000000e62e80000000000f8fffffffb4e686600000000fa4e686600000000f45b539503000000ae73de000000000011ce2d1200000000");
+
+        for bytes in [full, partial] {
+            let message = PriceUpdateV2::try_from_account_data(&bytes, &PYTH_FEED_OWNER).unwrap();
+            assert_eq!(&message.to_account_bytes()[..], &bytes[..]);
+
+            let mut dst = [0xffu8; PriceUpdateV2::LEN];
+            message.write_to(&mut dst);
+            assert_eq!(&dst[..], &bytes[..]);
+        }
+    }
+
+    #[test]
+    fn pricev2_write_to_account_slice() {
+        let message = PriceUpdateV2 {
+            write_authority: [1u8; 32],
+            verification_level: VerificationLevel::Partial { num_signatures: 3 },
+            price_message: PriceFeedMessage::get_feed_from_bytes(&[2u8; 84]),
+            posted_slot: 3,
+        };
+
+        let mut data = vec![0xffu8; PriceUpdateV2::LEN];
+        message.write_to((&mut data[..]).try_into().unwrap());
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data(&data, &PYTH_FEED_OWNER),
+            Ok(message)
+        );
+    }
+
+    #[test]
+    fn conf_ratio_bps() {
+        let price = Price {
+            price: -1000,
+            conf: 10,
+            exponent: -2,
+            publish_time: 0,
+        };
+        assert_eq!(price.conf_ratio_bps(), 100);
+        assert_eq!(Price { conf: 11, ..price }.conf_ratio_bps(), 110);
+        assert_eq!(Price { conf: 1, ..price }.conf_ratio_bps(), 10);
+        assert_eq!(Price { price: 3, ..price }.conf_ratio_bps(), 33334);
+        assert_eq!(Price { price: 0, ..price }.conf_ratio_bps(), u64::MAX);
+        assert_eq!(
+            Price {
+                price: 1,
+                conf: u64::MAX,
+                ..price
+            }
+            .conf_ratio_bps(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_get_price_with_max_conf_ratio() {
+        let price_update = MockPriceUpdate::new()
+            .price(1000)
+            .conf(25)
+            .ema_price(1000)
+            .ema_conf(5)
+            .age_seconds(2000, 10)
+            .build();
+
+        assert_eq!(
+            price_update
+                .get_price_no_older_than_with_max_conf_ratio(
+                    2000,
+                    30,
+                    None,
+                    VerificationLevel::Full,
+                    250
+                )
+                .map(|price| price.conf),
+            Ok(25)
+        );
+        assert_eq!(
+            price_update.get_price_no_older_than_with_max_conf_ratio(
+                2000,
+                30,
+                None,
+                VerificationLevel::Full,
+                249
+            ),
+            Err(GetPriceError::ConfidenceTooWide {
+                conf_ratio_bps: 250
+            })
+        );
+        assert!(price_update
+            .get_ema_price_no_older_than_with_max_conf_ratio(
+                2000,
+                30,
+                None,
+                VerificationLevel::Full,
+                50
+            )
+            .is_ok());
+        assert_eq!(
+            price_update.get_ema_price_no_older_than_with_max_conf_ratio(
+                2000,
+                30,
+                None,
+                VerificationLevel::Full,
+                49
+            ),
+            Err(GetPriceError::ConfidenceTooWide { conf_ratio_bps: 50 })
+        );
+        // Staleness is still reported first
+        assert_eq!(
+            price_update.get_price_no_older_than_with_max_conf_ratio(
+                2000,
+                5,
+                None,
+                VerificationLevel::Full,
+                0
+            ),
+            Err(GetPriceError::PriceTooOld)
+        );
+    }
+
+    #[test]
+    fn test_get_price_with_max_future_skew() {
+        let now = 2000;
+        let from_future = MockPriceUpdate::new().age_seconds(now, -10).build();
+
+        // Without a skew window, a price from the future is always fresh
+        assert!(from_future.get_price_no_older_than(now, 0, None).is_ok());
+        assert!(from_future
+            .get_price_no_older_than_with_max_future_skew(
+                now,
+                30,
+                10,
+                None,
+                VerificationLevel::Full
+            )
+            .is_ok());
+        assert_eq!(
+            from_future.get_price_no_older_than_with_max_future_skew(
+                now,
+                30,
+                9,
+                None,
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::PriceFromFuture)
+        );
+        assert_eq!(
+            from_future.get_ema_price_no_older_than_with_max_future_skew(
+                now,
+                30,
+                9,
+                None,
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::PriceFromFuture)
+        );
+        assert_eq!(
+            from_future.get_price_no_older_than_with_max_future_skew(
+                now,
+                30,
+                10,
+                None,
+                VerificationLevel::Partial { num_signatures: 5 }
+            ),
+            Ok(from_future.get_price_unchecked(None).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_freshness_extreme_inputs() {
+        let price = MockPriceUpdate::new()
+            .publish_time(i64::MIN)
+            .build()
+            .get_price_unchecked(None)
+            .unwrap();
+
+        // Used to panic converting `maximum_age` to an i64
+        assert_eq!(price.check_freshness(i64::MAX, u64::MAX, Some(0)), Ok(()));
+        assert_eq!(
+            price.check_freshness(i64::MAX, i64::MAX as u64, None),
+            Err(GetPriceError::PriceTooOld)
+        );
+        assert_eq!(price.check_freshness(i64::MIN, 0, Some(0)), Ok(()));
+        assert_eq!(price.age_seconds(i64::MAX), i64::MAX);
+
+        let future = Price {
+            publish_time: i64::MAX,
+            ..price
+        };
+        assert_eq!(future.age_seconds(i64::MIN), i64::MIN);
+        assert_eq!(future.age_seconds(i64::MAX - 3), -3);
+        assert_eq!(future.check_freshness(i64::MIN, 0, Some(u64::MAX)), Ok(()));
+        assert_eq!(
+            future.check_freshness(i64::MIN, 0, Some(i64::MAX as u64)),
+            Err(GetPriceError::PriceFromFuture)
+        );
+        assert_eq!(
+            MockPriceUpdate::new()
+                .age_seconds(0, 7)
+                .build()
+                .get_ema_price_unchecked(None)
+                .unwrap()
+                .age_seconds(0),
+            7
+        );
+    }
+
+    #[test]
+    fn test_get_price_no_older_than_slots() {
+        let price_update = MockPriceUpdate::new()
+            .posted_slot(1_000)
+            .age_seconds(2000, 10)
+            .build();
+
+        assert_eq!(price_update.posted_slot_age(1_025), 25);
+        assert_eq!(price_update.posted_slot_age(999), 0);
+        assert!(price_update
+            .get_price_no_older_than_slots(1_025, 25, Some(&SOL_USD), VerificationLevel::Full)
+            .is_ok());
+        assert_eq!(
+            price_update.get_price_no_older_than_slots(
+                1_026,
+                25,
+                Some(&SOL_USD),
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::PostedSlotTooOld)
+        );
+        assert_eq!(
+            price_update.get_ema_price_no_older_than_slots(
+                1_026,
+                25,
+                None,
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::PostedSlotTooOld)
+        );
+        assert_eq!(
+            price_update.get_price_no_older_than_slots(
+                1_000,
+                25,
+                Some(&[0u8; 32]),
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::MismatchedFeedId)
+        );
+    }
+
+    #[test]
+    fn test_get_price_no_older_than_with_slots() {
+        let price_update = MockPriceUpdate::new()
+            .posted_slot(1_000)
+            .age_seconds(2000, 10)
+            .build();
+        let level = VerificationLevel::Full;
+
+        assert!(price_update
+            .get_price_no_older_than_with_slots(2000, 10, 1_025, 25, None, level)
+            .is_ok());
+        assert!(price_update
+            .get_ema_price_no_older_than_with_slots(2000, 10, 1_025, 25, None, level)
+            .is_ok());
+        assert_eq!(
+            price_update.get_price_no_older_than_with_slots(2000, 10, 1_026, 25, None, level),
+            Err(GetPriceError::PostedSlotTooOld)
+        );
+        assert_eq!(
+            price_update.get_price_no_older_than_with_slots(2000, 9, 1_026, 25, None, level),
+            Err(GetPriceError::PriceTooOld)
+        );
+    }
 }
@@ -0,0 +1,249 @@
+use crate::{
+    byte_utils::{PubkeyBytes, DISCRIMINATOR_AS_BYTES},
+    error::ParseError,
+    messages::{FeedId, PriceFeedMessage},
+    price_update::{PriceUpdateV2, VerificationLevel},
+    pythnet::PYTH_FEED_OWNER,
+};
+
+/// A borrowed, zero-copy view over the bytes of a `PriceUpdateV2` account.
+///
+/// The layout is validated once on construction (including the 1 vs 2 byte `VerificationLevel`
+/// shift), which borrows every field as a fixed-size array so the accessors cannot fail. Prefer this
+/// over [`PriceUpdateV2`] in hot instructions that only need a couple of fields from many oracle
+/// accounts.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PriceUpdateV2Ref<'a> {
+    write_authority: &'a PubkeyBytes,
+    verification_level: VerificationLevel,
+    feed_id: &'a FeedId,
+    price: &'a [u8; 8],
+    conf: &'a [u8; 8],
+    exponent: &'a [u8; 4],
+    publish_time: &'a [u8; 8],
+    prev_publish_time: &'a [u8; 8],
+    ema_price: &'a [u8; 8],
+    ema_conf: &'a [u8; 8],
+    posted_slot: &'a [u8; 8],
+}
+
+/// Borrow the `N` bytes of `v` starting at `start`.
+fn array_at<const N: usize>(v: &[u8], start: usize) -> Result<&[u8; N], ParseError> {
+    v.get(start..start + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ParseError::Truncated {
+            expected: start + N,
+            actual: v.len(),
+        })
+}
+
+impl<'a> PriceUpdateV2Ref<'a> {
+    /// Wrap the account data without the 8-byte discriminator, i.e. `&data[8..]`. Any bytes after
+    /// the posted slot are padding and are accepted as long as the total does not exceed
+    /// `PriceUpdateV2::LEN - 8`.
+    pub fn try_from_bytes(v: &'a [u8]) -> Result<Self, ParseError> {
+        const MAX_LEN: usize = PriceUpdateV2::LEN - 8;
+        if v.len() > MAX_LEN {
+            return Err(ParseError::TrailingBytes {
+                expected: MAX_LEN,
+                actual: v.len(),
+            });
+        }
+        if v.len() < 33 {
+            return Err(ParseError::Truncated {
+                expected: 33,
+                actual: v.len(),
+            });
+        }
+
+        // If VerificationLevel::Full (0x01) then only one byte is used, otherwise 2 bytes.
+        let message_start = 32 + VerificationLevel::encoded_len_from_tag(v[32])?;
+        let slot_start = message_start + PriceFeedMessage::LEN;
+        let end = slot_start + 8;
+        if v.len() < end {
+            return Err(ParseError::Truncated {
+                expected: end,
+                actual: v.len(),
+            });
+        }
+
+        Ok(PriceUpdateV2Ref {
+            write_authority: array_at(v, 0)?,
+            verification_level: VerificationLevel::try_get_verification_from_bytes(
+                &v[32..message_start],
+            )?,
+            feed_id: array_at(v, message_start)?,
+            price: array_at(v, message_start + 32)?,
+            conf: array_at(v, message_start + 40)?,
+            exponent: array_at(v, message_start + 48)?,
+            publish_time: array_at(v, message_start + 52)?,
+            prev_publish_time: array_at(v, message_start + 60)?,
+            ema_price: array_at(v, message_start + 68)?,
+            ema_conf: array_at(v, message_start + 76)?,
+            posted_slot: array_at(v, slot_start)?,
+        })
+    }
+
+    /// Wrap the full account data, including the discriminator. Checks that the account is owned
+    /// by [`PYTH_FEED_OWNER`], is exactly `PriceUpdateV2::LEN` bytes, and starts with
+    /// [`DISCRIMINATOR_AS_BYTES`].
+    pub fn try_from_account_data(data: &'a [u8], owner: &PubkeyBytes) -> Result<Self, ParseError> {
+        Self::try_from_account_data_with_owners(data, owner, &[PYTH_FEED_OWNER])
+    }
+
+    /// Same as [`PriceUpdateV2Ref::try_from_account_data`], but accepts any owner in
+    /// `allowed_owners` instead of only [`PYTH_FEED_OWNER`].
+    pub fn try_from_account_data_with_owners(
+        data: &'a [u8],
+        owner: &PubkeyBytes,
+        allowed_owners: &[PubkeyBytes],
+    ) -> Result<Self, ParseError> {
+        if !allowed_owners.contains(owner) {
+            return Err(ParseError::InvalidOwner);
+        }
+        if data.len() < PriceUpdateV2::LEN {
+            return Err(ParseError::Truncated {
+                expected: PriceUpdateV2::LEN,
+                actual: data.len(),
+            });
+        }
+        if data.len() > PriceUpdateV2::LEN {
+            return Err(ParseError::TrailingBytes {
+                expected: PriceUpdateV2::LEN,
+                actual: data.len(),
+            });
+        }
+        if data[..8] != DISCRIMINATOR_AS_BYTES {
+            return Err(ParseError::InvalidDiscriminator);
+        }
+
+        Self::try_from_bytes(&data[8..])
+    }
+
+    pub fn write_authority(&self) -> &'a PubkeyBytes {
+        self.write_authority
+    }
+
+    pub fn verification_level(&self) -> VerificationLevel {
+        self.verification_level
+    }
+
+    pub fn feed_id(&self) -> &'a FeedId {
+        self.feed_id
+    }
+
+    pub fn price(&self) -> i64 {
+        i64::from_le_bytes(*self.price)
+    }
+
+    pub fn conf(&self) -> u64 {
+        u64::from_le_bytes(*self.conf)
+    }
+
+    pub fn exponent(&self) -> i32 {
+        i32::from_le_bytes(*self.exponent)
+    }
+
+    /// The timestamp of this price update in seconds
+    pub fn publish_time(&self) -> i64 {
+        i64::from_le_bytes(*self.publish_time)
+    }
+
+    pub fn prev_publish_time(&self) -> i64 {
+        i64::from_le_bytes(*self.prev_publish_time)
+    }
+
+    pub fn ema_price(&self) -> i64 {
+        i64::from_le_bytes(*self.ema_price)
+    }
+
+    pub fn ema_conf(&self) -> u64 {
+        u64::from_le_bytes(*self.ema_conf)
+    }
+
+    pub fn posted_slot(&self) -> u64 {
+        u64::from_le_bytes(*self.posted_slot)
+    }
+
+    /// Decode the whole price message. This copies every field, use the individual accessors if
+    /// you only need some of them.
+    pub fn price_message(&self) -> PriceFeedMessage {
+        PriceFeedMessage {
+            feed_id: *self.feed_id,
+            price: self.price(),
+            conf: self.conf(),
+            exponent: self.exponent(),
+            publish_time: self.publish_time(),
+            prev_publish_time: self.prev_publish_time(),
+            ema_price: self.ema_price(),
+            ema_conf: self.ema_conf(),
+        }
+    }
+
+    /// Copy this view into an owned [`PriceUpdateV2`].
+    pub fn to_price_update(&self) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: *self.write_authority,
+            verification_level: self.verification_level,
+            price_message: self.price_message(),
+            posted_slot: self.posted_slot(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PriceUpdateV2Ref<'a> {
+    type Error = ParseError;
+
+    fn try_from(v: &'a [u8]) -> Result<Self, Self::Error> {
+        PriceUpdateV2Ref::try_from_bytes(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_utils::hex_to_bytes;
+
+    use super::*;
+
+    #[test]
+    fn ref_matches_owned_full() {
+        // From mainnet: https://solana.fm/address/7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE
+        let hex_data = "22f123639d7ef4cd60314704340deddf371fd42472148f248e9d1a6d1a5eb2ac3acd8b7fd5d6b24301ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d107fc8e30300000049a7550100000000f8ffffff314963660000000030496366000000008cc427ed030000009b14030100000000dded1e100000000000";
+        let bytes = hex_to_bytes(hex_data);
+
+        let view = PriceUpdateV2Ref::try_from_account_data(&bytes, &PYTH_FEED_OWNER).unwrap();
+        let owned = PriceUpdateV2::get_price_update_v2_from_bytes(&bytes[8..]);
+
+        assert_eq!(view.verification_level(), VerificationLevel::Full);
+        assert_eq!(view.feed_id(), &owned.price_message.feed_id);
+        assert_eq!(view.price(), 16706469648);
+        assert_eq!(view.conf(), 22390601);
+        assert_eq!(view.exponent(), -8);
+        assert_eq!(view.publish_time(), 1717782833);
+        assert_eq!(view.posted_slot(), 270462429);
+        assert_eq!(view.to_price_update(), owned);
+    }
+
+    #[test]
+    fn ref_matches_owned_partial() {
+        // From devnet: https://solana.fm/address/DMzo13MxzhrU1dbtJRCxdLoa9zwWowBJu17KhRQ5tLWM
+        let hex_data =  "22f123639d7ef4cd0d881b9f67c8cb3d52fd2eb27d13c20951d199212b75021d55ecbf5e183b8cdb0005ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d5eaf3497030000000e62e80000000000f8fffffffb4e686600000000fa4e686600000000f45b539503000000ae73de000000000011ce2d1200000000";
+        let bytes = hex_to_bytes(hex_data);
+
+        let view = PriceUpdateV2Ref::try_from(&bytes[8..]).unwrap();
+
+        assert_eq!(
+            view.verification_level(),
+            VerificationLevel::Partial { num_signatures: 5 }
+        );
+        assert_eq!(view.price(), 15421714270);
+        assert_eq!(view.conf(), 15229454);
+        assert_eq!(view.ema_price(), 15390170100);
+        assert_eq!(view.ema_conf(), 14578606);
+        assert_eq!(view.posted_slot(), 304991761);
+        assert_eq!(
+            view.to_price_update(),
+            PriceUpdateV2::get_price_update_v2_from_bytes(&bytes[8..])
+        );
+    }
+}
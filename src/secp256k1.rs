@@ -0,0 +1,495 @@
+//! Dependency-free secp256k1 public key recovery, enough to check Wormhole guardian signatures
+//! off-chain.
+//!
+//! Nothing here is constant-time: only public data (signatures and digests of published VAAs)
+//! goes through it, so it must not be reused for signing with secret keys.
+
+/// A 256-bit integer as four 64-bit limbs, least significant first
+type U256 = [u64; 4];
+
+/// A 512-bit integer as eight 64-bit limbs, least significant first
+type U512 = [u64; 8];
+
+const ZERO: U256 = [0; 4];
+const ONE: U256 = [1, 0, 0, 0];
+const SEVEN: U256 = [7, 0, 0, 0];
+
+/// The field of coordinates, modulo `p = 2^256 - 2^32 - 977`
+const FIELD: Modulus = Modulus {
+    modulus: [
+        0xFFFFFFFEFFFFFC2F,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+    ],
+    complement: [0x1000003D1, 0, 0, 0],
+};
+
+/// The scalars, modulo the order `n` of the group
+const SCALAR: Modulus = Modulus {
+    modulus: [
+        0xBFD25E8CD0364141,
+        0xBAAEDCE6AF48A03B,
+        0xFFFFFFFFFFFFFFFE,
+        0xFFFFFFFFFFFFFFFF,
+    ],
+    complement: [0x402DA1732FC9BEBF, 0x4551231950B75FC4, 0x1, 0],
+};
+
+/// `(p + 1) / 4`, as `p ≡ 3 (mod 4)` square roots are a single exponentiation
+const SQRT_EXPONENT: U256 = [
+    0xFFFFFFFFBFFFFF0C,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+    0x3FFFFFFFFFFFFFFF,
+];
+
+/// The generator point
+const G: Affine = Affine {
+    x: [
+        0x59F2815B16F81798,
+        0x029BFCDB2DCE28D9,
+        0x55A06295CE870B07,
+        0x79BE667EF9DCBBAC,
+    ],
+    y: [
+        0x9C47D08FFB10D4B8,
+        0xFD17B448A6855419,
+        0x5DA4FBFC0E1108A8,
+        0x483ADA7726A3C465,
+    ],
+};
+
+fn from_be_bytes(bytes: &[u8]) -> U256 {
+    let mut value = ZERO;
+    for (limb, chunk) in value.iter_mut().rev().zip(bytes.chunks_exact(8)) {
+        let mut be = [0u8; 8];
+        be.copy_from_slice(chunk);
+        *limb = u64::from_be_bytes(be);
+    }
+    value
+}
+
+fn to_be_bytes(value: &U256, bytes: &mut [u8]) {
+    for (chunk, limb) in bytes.chunks_exact_mut(8).zip(value.iter().rev()) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+}
+
+fn is_zero(value: &U256) -> bool {
+    *value == ZERO
+}
+
+fn less_than(a: &U256, b: &U256) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
+
+/// `a + b`, and whether it overflowed
+fn add(a: &U256, b: &U256) -> (U256, bool) {
+    let mut sum = ZERO;
+    let mut carry = false;
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(u64::from(carry));
+        sum[i] = s;
+        carry = c1 || c2;
+    }
+    (sum, carry)
+}
+
+/// `a - b`, and whether it underflowed
+fn sub(a: &U256, b: &U256) -> (U256, bool) {
+    let mut difference = ZERO;
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(u64::from(borrow));
+        difference[i] = d;
+        borrow = b1 || b2;
+    }
+    (difference, borrow)
+}
+
+fn mul_wide(a: &U256, b: &U256) -> U512 {
+    let mut product = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let t = u128::from(a[i]) * u128::from(b[j]) + u128::from(product[i + j]) + carry;
+            product[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    product
+}
+
+/// Arithmetic modulo a prime just below `2^256`
+struct Modulus {
+    modulus: U256,
+    /// `2^256 - modulus`, small enough to fold the high half of products into the low half
+    complement: U256,
+}
+
+impl Modulus {
+    /// Reduce a value below `2^256`
+    fn reduce(&self, mut value: U256) -> U256 {
+        while !less_than(&value, &self.modulus) {
+            value = sub(&value, &self.modulus).0;
+        }
+        value
+    }
+
+    /// Reduce using `hi * 2^256 + lo ≡ hi * complement + lo`, until the high half is zero
+    fn reduce_wide(&self, mut value: U512) -> U256 {
+        loop {
+            let mut lo = ZERO;
+            let mut hi = ZERO;
+            lo.copy_from_slice(&value[..4]);
+            hi.copy_from_slice(&value[4..]);
+            if is_zero(&hi) {
+                return self.reduce(lo);
+            }
+            value = mul_wide(&hi, &self.complement);
+            let mut carry = false;
+            for (i, limb) in value.iter_mut().enumerate() {
+                let (s, c1) = limb.overflowing_add(lo.get(i).copied().unwrap_or(0));
+                let (s, c2) = s.overflowing_add(u64::from(carry));
+                *limb = s;
+                carry = c1 || c2;
+            }
+        }
+    }
+
+    fn add(&self, a: &U256, b: &U256) -> U256 {
+        let (sum, carry) = add(a, b);
+        if carry || !less_than(&sum, &self.modulus) {
+            sub(&sum, &self.modulus).0
+        } else {
+            sum
+        }
+    }
+
+    fn sub(&self, a: &U256, b: &U256) -> U256 {
+        let (difference, borrow) = sub(a, b);
+        if borrow {
+            add(&difference, &self.modulus).0
+        } else {
+            difference
+        }
+    }
+
+    fn neg(&self, a: &U256) -> U256 {
+        self.sub(&ZERO, a)
+    }
+
+    fn mul(&self, a: &U256, b: &U256) -> U256 {
+        self.reduce_wide(mul_wide(a, b))
+    }
+
+    fn square(&self, a: &U256) -> U256 {
+        self.mul(a, a)
+    }
+
+    fn pow(&self, base: &U256, exponent: &U256) -> U256 {
+        let mut result = ONE;
+        for bit in (0..256).rev() {
+            result = self.square(&result);
+            if exponent[bit / 64] >> (bit % 64) & 1 == 1 {
+                result = self.mul(&result, base);
+            }
+        }
+        result
+    }
+
+    /// The inverse of a non-zero value, by Fermat's little theorem
+    fn inv(&self, a: &U256) -> U256 {
+        let exponent = sub(&self.modulus, &[2, 0, 0, 0]).0;
+        self.pow(a, &exponent)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Affine {
+    x: U256,
+    y: U256,
+}
+
+/// A point in Jacobian coordinates, `(x / z^2, y / z^3)`. `z = 0` is the point at infinity.
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Jacobian {
+    x: U256,
+    y: U256,
+    z: U256,
+}
+
+impl Jacobian {
+    const INFINITY: Jacobian = Jacobian {
+        x: ONE,
+        y: ONE,
+        z: ZERO,
+    };
+
+    fn from_affine(point: &Affine) -> Self {
+        Jacobian {
+            x: point.x,
+            y: point.y,
+            z: ONE,
+        }
+    }
+
+    fn is_infinity(&self) -> bool {
+        is_zero(&self.z)
+    }
+
+    fn to_affine(self) -> Option<Affine> {
+        if self.is_infinity() {
+            return None;
+        }
+        let z_inv = FIELD.inv(&self.z);
+        let z_inv2 = FIELD.square(&z_inv);
+        Some(Affine {
+            x: FIELD.mul(&self.x, &z_inv2),
+            y: FIELD.mul(&self.y, &FIELD.mul(&z_inv2, &z_inv)),
+        })
+    }
+
+    fn double(&self) -> Self {
+        if self.is_infinity() || is_zero(&self.y) {
+            return Self::INFINITY;
+        }
+        let a = FIELD.square(&self.x);
+        let b = FIELD.square(&self.y);
+        let c = FIELD.square(&b);
+        let x_plus_b = FIELD.add(&self.x, &b);
+        let d = FIELD.sub(&FIELD.sub(&FIELD.square(&x_plus_b), &a), &c);
+        let d = FIELD.add(&d, &d);
+        let e = FIELD.add(&FIELD.add(&a, &a), &a);
+        let f = FIELD.square(&e);
+        let x = FIELD.sub(&f, &FIELD.add(&d, &d));
+        let c8 = FIELD.add(&c, &c);
+        let c8 = FIELD.add(&c8, &c8);
+        let c8 = FIELD.add(&c8, &c8);
+        let y = FIELD.sub(&FIELD.mul(&e, &FIELD.sub(&d, &x)), &c8);
+        let z = FIELD.mul(&self.y, &self.z);
+        Jacobian {
+            x,
+            y,
+            z: FIELD.add(&z, &z),
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        if self.is_infinity() {
+            return *other;
+        }
+        if other.is_infinity() {
+            return *self;
+        }
+        let z1z1 = FIELD.square(&self.z);
+        let z2z2 = FIELD.square(&other.z);
+        let u1 = FIELD.mul(&self.x, &z2z2);
+        let u2 = FIELD.mul(&other.x, &z1z1);
+        let s1 = FIELD.mul(&self.y, &FIELD.mul(&other.z, &z2z2));
+        let s2 = FIELD.mul(&other.y, &FIELD.mul(&self.z, &z1z1));
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double()
+            } else {
+                Self::INFINITY
+            };
+        }
+        let h = FIELD.sub(&u2, &u1);
+        let r = FIELD.sub(&s2, &s1);
+        let h2 = FIELD.square(&h);
+        let h3 = FIELD.mul(&h, &h2);
+        let u1h2 = FIELD.mul(&u1, &h2);
+        let x = FIELD.sub(&FIELD.sub(&FIELD.square(&r), &h3), &FIELD.add(&u1h2, &u1h2));
+        let y = FIELD.sub(&FIELD.mul(&r, &FIELD.sub(&u1h2, &x)), &FIELD.mul(&s1, &h3));
+        Jacobian {
+            x,
+            y,
+            z: FIELD.mul(&h, &FIELD.mul(&self.z, &other.z)),
+        }
+    }
+
+    /// `a * p + b * q`, sharing the doublings between both products
+    fn double_mul(a: &U256, p: &Affine, b: &U256, q: &Affine) -> Self {
+        let p = Self::from_affine(p);
+        let q = Self::from_affine(q);
+        let p_plus_q = p.add(&q);
+        let mut result = Self::INFINITY;
+        for bit in (0..256).rev() {
+            result = result.double();
+            let bits = (a[bit / 64] >> (bit % 64) & 1, b[bit / 64] >> (bit % 64) & 1);
+            match bits {
+                (1, 1) => result = result.add(&p_plus_q),
+                (1, _) => result = result.add(&p),
+                (_, 1) => result = result.add(&q),
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+/// Recover the uncompressed public key, `x || y` without the `0x04` prefix, that produced a
+/// recoverable signature `r || s || v` over `digest`.
+///
+/// The recovery id `v` may be 0 or 1, or 27 or 28 as used by Ethereum. Returns `None` if the
+/// signature is malformed or does not correspond to any key. As with `ecrecover`, a signature
+/// always recovers *some* key, so the result must be compared with the expected signer.
+pub fn recover_public_key(digest: &[u8; 32], signature: &[u8; 65]) -> Option<[u8; 64]> {
+    let r = from_be_bytes(&signature[..32]);
+    let s = from_be_bytes(&signature[32..64]);
+    let y_is_odd = match signature[64] {
+        0 | 27 => false,
+        1 | 28 => true,
+        _ => return None,
+    };
+    if is_zero(&r)
+        || is_zero(&s)
+        || !less_than(&r, &SCALAR.modulus)
+        || !less_than(&s, &SCALAR.modulus)
+    {
+        return None;
+    }
+
+    // The point R of the signature, with x = r. r < n < p, so it is a valid coordinate.
+    let y_squared = FIELD.add(&FIELD.mul(&FIELD.square(&r), &r), &SEVEN);
+    let mut y = FIELD.pow(&y_squared, &SQRT_EXPONENT);
+    if FIELD.square(&y) != y_squared {
+        return None;
+    }
+    if (y[0] & 1 == 1) != y_is_odd {
+        y = FIELD.neg(&y);
+    }
+    let point_r = Affine { x: r, y };
+
+    // Q = r^-1 (s R - e G)
+    let e = SCALAR.reduce(from_be_bytes(digest));
+    let r_inv = SCALAR.inv(&r);
+    let u1 = SCALAR.neg(&SCALAR.mul(&e, &r_inv));
+    let u2 = SCALAR.mul(&s, &r_inv);
+    let q = Jacobian::double_mul(&u1, &G, &u2, &point_r).to_affine()?;
+
+    let mut public_key = [0u8; 64];
+    to_be_bytes(&q.x, &mut public_key[..32]);
+    to_be_bytes(&q.y, &mut public_key[32..]);
+    Some(public_key)
+}
+
+/// Sign with a deterministic nonce derived from the key and digest, for tests only.
+#[cfg(test)]
+pub(crate) fn sign(digest: &[u8; 32], secret_key: &[u8; 32]) -> [u8; 65] {
+    let mut nonce_seed = [0u8; 64];
+    nonce_seed[..32].copy_from_slice(secret_key);
+    nonce_seed[32..].copy_from_slice(digest);
+    let d = SCALAR.reduce(from_be_bytes(secret_key));
+    let e = SCALAR.reduce(from_be_bytes(digest));
+    let k = SCALAR.reduce(from_be_bytes(&crate::keccak::keccak256(&nonce_seed)));
+
+    let point = Jacobian::double_mul(&k, &G, &ZERO, &G)
+        .to_affine()
+        .expect("nonce is not zero");
+    let r = SCALAR.reduce(point.x);
+    let s = SCALAR.mul(&SCALAR.inv(&k), &SCALAR.add(&e, &SCALAR.mul(&r, &d)));
+
+    let mut signature = [0u8; 65];
+    to_be_bytes(&r, &mut signature[..32]);
+    to_be_bytes(&s, &mut signature[32..64]);
+    signature[64] = (point.y[0] & 1) as u8;
+    signature
+}
+
+/// The uncompressed public key of `secret_key`, for tests only.
+#[cfg(test)]
+pub(crate) fn public_key(secret_key: &[u8; 32]) -> [u8; 64] {
+    let d = SCALAR.reduce(from_be_bytes(secret_key));
+    let point = Jacobian::double_mul(&d, &G, &ZERO, &G)
+        .to_affine()
+        .expect("secret key is not zero");
+    let mut public_key = [0u8; 64];
+    to_be_bytes(&point.x, &mut public_key[..32]);
+    to_be_bytes(&point.y, &mut public_key[32..]);
+    public_key
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_utils::hex_to_bytes;
+
+    use super::*;
+
+    const DIGEST: [u8; 32] = [
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+        26, 27, 28, 29, 30, 31, 32,
+    ];
+    const SIGNATURE: &str = "bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020d\
+                             9a77b754d15dcc383a6db645c118b58268f7dfb959ea7ae0a1e8212a85f9b6f4";
+    const PUBLIC_KEY: &str = "4e3b81af9c2234cad09d679ce6035ed1392347ce64ce405f5dcd36228a25de6e\
+                              47fd35c4215d1edf53e6f83de344615ce719bdb0fd878f6ed76f06dd277956de";
+
+    fn signature(rs: &str, v: u8) -> [u8; 65] {
+        let mut signature = [0u8; 65];
+        signature[..64].copy_from_slice(&hex_to_bytes(rs));
+        signature[64] = v;
+        signature
+    }
+
+    #[test]
+    fn recover_known_signature() {
+        let expected = hex_to_bytes(PUBLIC_KEY);
+        for v in [0, 27] {
+            let recovered = recover_public_key(&DIGEST, &signature(SIGNATURE, v)).unwrap();
+            assert_eq!(recovered.to_vec(), expected);
+        }
+        // The other recovery id gives another key
+        let other = recover_public_key(&DIGEST, &signature(SIGNATURE, 1)).unwrap();
+        assert_ne!(other.to_vec(), expected);
+        // So does another digest
+        let other = recover_public_key(&[0; 32], &signature(SIGNATURE, 0)).unwrap();
+        assert_ne!(other.to_vec(), expected);
+    }
+
+    #[test]
+    fn reject_malformed_signatures() {
+        let valid = signature(SIGNATURE, 0);
+        let mut bad_v = valid;
+        bad_v[64] = 2;
+        let mut zero_r = valid;
+        zero_r[..32].fill(0);
+        let mut zero_s = valid;
+        zero_s[32..64].fill(0);
+        let mut s_above_order = valid;
+        s_above_order[32..64].fill(0xff);
+        // x = 5 is not the coordinate of any point, since 5^3 + 7 is not a square
+        let mut no_point = valid;
+        no_point[..32].fill(0);
+        no_point[31] = 5;
+        for signature in [bad_v, zero_r, zero_s, s_above_order, no_point] {
+            assert_eq!(recover_public_key(&DIGEST, &signature), None);
+        }
+    }
+
+    #[test]
+    fn sign_and_recover() {
+        let mut secret_key = [0u8; 32];
+        secret_key[31] = 1;
+        // The secret key 1 has the generator as public key
+        let mut generator = [0u8; 64];
+        to_be_bytes(&G.x, &mut generator[..32]);
+        to_be_bytes(&G.y, &mut generator[32..]);
+        assert_eq!(public_key(&secret_key), generator);
+
+        for seed in 1..8u8 {
+            let secret_key = crate::keccak::keccak256(&[seed]);
+            let digest = crate::keccak::keccak256(&[seed, seed]);
+            let signature = sign(&digest, &secret_key);
+            assert_eq!(
+                recover_public_key(&digest, &signature),
+                Some(public_key(&secret_key))
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    error::Result,
+    messages::FeedId,
+    price_update::{EmaPrice, Price, PriceUpdateV2, VerificationLevel},
+    price_update_ref::PriceUpdateV2Ref,
+};
+
+/// Anything a price can be read from. Code that only needs prices can be generic over this trait
+/// instead of a concrete account type, which also makes it easy to substitute a test double.
+///
+/// None of these methods check freshness, verification or the feed id, use
+/// [`PriceValidationPolicy::validate`](crate::policy::PriceValidationPolicy::validate) for that.
+pub trait PriceSource {
+    /// The feed this source prices
+    fn feed_id(&self) -> FeedId;
+
+    /// How thoroughly the price was verified
+    fn verification(&self) -> VerificationLevel;
+
+    /// The slot at which the price was made available
+    fn posted_slot(&self) -> u64;
+
+    /// The price as of unix time `now`. Sources that store the latest published price, like Pyth
+    /// accounts, return it as is.
+    fn price(&self, now: i64) -> Result<Price>;
+
+    /// The EMA price as of unix time `now`
+    fn ema_price(&self, now: i64) -> Result<EmaPrice>;
+}
+
+impl PriceSource for PriceUpdateV2 {
+    fn feed_id(&self) -> FeedId {
+        self.price_message.feed_id
+    }
+
+    fn verification(&self) -> VerificationLevel {
+        self.verification_level
+    }
+
+    fn posted_slot(&self) -> u64 {
+        self.posted_slot
+    }
+
+    fn price(&self, _now: i64) -> Result<Price> {
+        self.get_price_unchecked(None)
+    }
+
+    fn ema_price(&self, _now: i64) -> Result<EmaPrice> {
+        self.get_ema_price_unchecked(None)
+    }
+}
+
+impl PriceSource for PriceUpdateV2Ref<'_> {
+    fn feed_id(&self) -> FeedId {
+        *PriceUpdateV2Ref::feed_id(self)
+    }
+
+    fn verification(&self) -> VerificationLevel {
+        self.verification_level()
+    }
+
+    fn posted_slot(&self) -> u64 {
+        PriceUpdateV2Ref::posted_slot(self)
+    }
+
+    fn price(&self, _now: i64) -> Result<Price> {
+        Ok(Price {
+            price: PriceUpdateV2Ref::price(self),
+            conf: self.conf(),
+            exponent: self.exponent(),
+            publish_time: self.publish_time(),
+        })
+    }
+
+    fn ema_price(&self, _now: i64) -> Result<EmaPrice> {
+        Ok(EmaPrice {
+            ema_price: PriceUpdateV2Ref::ema_price(self),
+            ema_conf: self.ema_conf(),
+            exponent: self.exponent(),
+            publish_time: self.publish_time(),
+        })
+    }
+}
+
+impl<S: PriceSource + ?Sized> PriceSource for &S {
+    fn feed_id(&self) -> FeedId {
+        (**self).feed_id()
+    }
+
+    fn verification(&self) -> VerificationLevel {
+        (**self).verification()
+    }
+
+    fn posted_slot(&self) -> u64 {
+        (**self).posted_slot()
+    }
+
+    fn price(&self, now: i64) -> Result<Price> {
+        (**self).price(now)
+    }
+
+    fn ema_price(&self, now: i64) -> Result<EmaPrice> {
+        (**self).ema_price(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::GetPriceError, policy::PriceValidationPolicy, pythnet::PYTH_FEED_OWNER,
+        testing::MockPriceUpdate,
+    };
+
+    use super::*;
+
+    /// A source whose price moves by one unit per second, as a test double would
+    struct Drifting {
+        start: i64,
+    }
+
+    impl PriceSource for Drifting {
+        fn feed_id(&self) -> FeedId {
+            [7u8; 32]
+        }
+
+        fn verification(&self) -> VerificationLevel {
+            VerificationLevel::Full
+        }
+
+        fn posted_slot(&self) -> u64 {
+            0
+        }
+
+        fn price(&self, now: i64) -> Result<Price> {
+            if now < self.start {
+                return Err(GetPriceError::PriceFromFuture);
+            }
+            Ok(Price {
+                price: now - self.start,
+                conf: 0,
+                exponent: 0,
+                publish_time: now,
+            })
+        }
+
+        fn ema_price(&self, now: i64) -> Result<EmaPrice> {
+            let price = self.price(now)?;
+            Ok(EmaPrice {
+                ema_price: price.price,
+                ema_conf: price.conf,
+                exponent: price.exponent,
+                publish_time: price.publish_time,
+            })
+        }
+    }
+
+    fn read<S: PriceSource + ?Sized>(source: &S, now: i64) -> Result<(FeedId, Price)> {
+        let price = PriceValidationPolicy::new(30).validate(source, now)?;
+        Ok((source.feed_id(), price))
+    }
+
+    #[test]
+    fn owned_and_borrowed_updates_agree() {
+        let mock = MockPriceUpdate::new().partial(3).posted_slot(9);
+        let update = mock.build();
+        let data = mock.account_bytes();
+        let view = PriceUpdateV2Ref::try_from_account_data(&data, &PYTH_FEED_OWNER).unwrap();
+
+        assert_eq!(PriceSource::feed_id(&update), PriceSource::feed_id(&view));
+        assert_eq!(update.verification(), view.verification());
+        assert_eq!(PriceSource::posted_slot(&view), 9);
+        assert_eq!(PriceSource::price(&update, 0), PriceSource::price(&view, 0));
+        assert_eq!(
+            PriceSource::ema_price(&update, 0),
+            PriceSource::ema_price(&view, 0)
+        );
+    }
+
+    #[test]
+    fn generic_over_sources() {
+        let now = 1_700_000_000;
+        let update = MockPriceUpdate::new().age_seconds(now, 5).build();
+        assert_eq!(
+            read(&update, now),
+            Ok((update.feed_id(), update.get_price_unchecked(None).unwrap()))
+        );
+
+        let drifting = Drifting { start: now - 50 };
+        assert_eq!(read(&drifting, now).unwrap().1.price, 50);
+        assert_eq!(
+            read(&drifting, now - 51),
+            Err(GetPriceError::PriceFromFuture)
+        );
+    }
+}
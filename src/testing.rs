@@ -0,0 +1,196 @@
+//! Utilities for writing tests against code that reads Pyth price accounts. Enabled by the
+//! `testing` feature.
+//!
+//! ```
+//! use pyth_min::testing::{MockPriceUpdate, SOL_USD};
+//!
+//! let now = 1_700_000_000;
+//! let update = MockPriceUpdate::new().feed(SOL_USD).price(150_00000000).age_seconds(now, 30);
+//!
+//! let price = update.build().get_price_no_older_than(now, 60, Some(&SOL_USD)).unwrap();
+//! assert_eq!(price.price, 150_00000000);
+//!
+//! // Ready to be stored as the data of a mock account owned by `pythnet::PYTH_FEED_OWNER`
+//! let data = update.account_bytes();
+//! ```
+
+use crate::{
+    messages::{FeedId, PriceFeedMessage},
+    price_update::{get_feed_id_from_hex, PriceUpdateV2, VerificationLevel},
+};
+
+/// SOL/USD feed id
+pub const SOL_USD: FeedId =
+    match get_feed_id_from_hex("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d") {
+        Ok(id) => id,
+        Err(_) => panic!("invalid SOL/USD feed id"),
+    };
+
+/// Fluent builder for `PriceUpdateV2` fixtures.
+///
+/// Defaults to a fully verified SOL/USD update of `100 ± 0.05` (exponent -8, same EMA), published
+/// at unix time 1_700_000_000 and posted at slot 1.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MockPriceUpdate {
+    update: PriceUpdateV2,
+}
+
+impl Default for MockPriceUpdate {
+    fn default() -> Self {
+        MockPriceUpdate {
+            update: PriceUpdateV2 {
+                write_authority: [0u8; 32],
+                verification_level: VerificationLevel::Full,
+                price_message: PriceFeedMessage {
+                    feed_id: SOL_USD,
+                    price: 100_00000000,
+                    conf: 5_000_000,
+                    exponent: -8,
+                    publish_time: 1_700_000_000,
+                    prev_publish_time: 1_699_999_999,
+                    ema_price: 100_00000000,
+                    ema_conf: 5_000_000,
+                },
+                posted_slot: 1,
+            },
+        }
+    }
+}
+
+impl MockPriceUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(mut self, feed_id: FeedId) -> Self {
+        self.update.price_message.feed_id = feed_id;
+        self
+    }
+
+    pub fn price(mut self, price: i64) -> Self {
+        self.update.price_message.price = price;
+        self
+    }
+
+    pub fn conf(mut self, conf: u64) -> Self {
+        self.update.price_message.conf = conf;
+        self
+    }
+
+    pub fn exponent(mut self, exponent: i32) -> Self {
+        self.update.price_message.exponent = exponent;
+        self
+    }
+
+    pub fn ema_price(mut self, ema_price: i64) -> Self {
+        self.update.price_message.ema_price = ema_price;
+        self
+    }
+
+    pub fn ema_conf(mut self, ema_conf: u64) -> Self {
+        self.update.price_message.ema_conf = ema_conf;
+        self
+    }
+
+    /// Sets the publish time, and the previous publish time to one second before it.
+    pub fn publish_time(mut self, publish_time: i64) -> Self {
+        self.update.price_message.publish_time = publish_time;
+        self.update.price_message.prev_publish_time = publish_time.saturating_sub(1);
+        self
+    }
+
+    /// Makes the update `age` seconds old as of `now`.
+    pub fn age_seconds(self, now: i64, age: i64) -> Self {
+        self.publish_time(now.saturating_sub(age))
+    }
+
+    /// Marks the update as only partially verified with `num_signatures` guardian signatures.
+    pub fn partial(mut self, num_signatures: u8) -> Self {
+        self.update.verification_level = VerificationLevel::Partial { num_signatures };
+        self
+    }
+
+    pub fn full(mut self) -> Self {
+        self.update.verification_level = VerificationLevel::Full;
+        self
+    }
+
+    pub fn write_authority(mut self, write_authority: [u8; 32]) -> Self {
+        self.update.write_authority = write_authority;
+        self
+    }
+
+    pub fn posted_slot(mut self, posted_slot: u64) -> Self {
+        self.update.posted_slot = posted_slot;
+        self
+    }
+
+    pub fn build(&self) -> PriceUpdateV2 {
+        self.update
+    }
+
+    /// The fixture serialized as on-chain account data, discriminator included.
+    pub fn account_bytes(&self) -> [u8; PriceUpdateV2::LEN] {
+        self.update.to_account_bytes()
+    }
+}
+
+impl From<MockPriceUpdate> for PriceUpdateV2 {
+    fn from(mock: MockPriceUpdate) -> Self {
+        mock.build()
+    }
+}
+
+/// Shorthand for building a `Price` in unit tests.
+#[cfg(test)]
+pub(crate) fn price(
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+) -> crate::price_update::Price {
+    crate::price_update::Price {
+        price,
+        conf,
+        exponent,
+        publish_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::GetPriceError, pythnet::PYTH_FEED_OWNER};
+
+    use super::*;
+
+    #[test]
+    fn mock_price_update_round_trips() {
+        let mock = MockPriceUpdate::new().price(-5).exponent(-2).partial(5).posted_slot(42);
+        let update = mock.build();
+
+        assert_eq!(update.price_message.price, -5);
+        assert_eq!(update.price_message.exponent, -2);
+        assert_eq!(
+            update.verification_level,
+            VerificationLevel::Partial { num_signatures: 5 }
+        );
+        assert_eq!(
+            PriceUpdateV2::try_from_account_data(&mock.account_bytes(), &PYTH_FEED_OWNER),
+            Ok(update)
+        );
+    }
+
+    #[test]
+    fn mock_price_update_age() {
+        let now = 2_000;
+        let update: PriceUpdateV2 = MockPriceUpdate::new().age_seconds(now, 31).into();
+
+        assert_eq!(update.price_message.publish_time, 1_969);
+        assert_eq!(update.price_message.prev_publish_time, 1_968);
+        assert_eq!(
+            update.get_price_no_older_than(now, 30, Some(&SOL_USD)),
+            Err(GetPriceError::PriceTooOld)
+        );
+        assert!(update.get_price_no_older_than(now, 31, Some(&SOL_USD)).is_ok());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    error::{GetPriceError, Result},
+    math::{div_round, mul_pow10_div, Rounding, BPS_DENOMINATOR},
+    price_update::Price,
+};
+
+/// Which side of a position a price is used for. Lending code should value what it holds at the
+/// bottom of the confidence interval and what it is owed at the top, so that uncertainty in the
+/// price always works against the borrower.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Side {
+    /// Assets, valued at `price - conf`, rounded down
+    Collateral,
+    /// Liabilities, valued at `price + conf`, rounded up
+    Debt,
+}
+
+impl Price {
+    /// The value of `amount` base units of a token with `mint_decimals` decimals, expressed in
+    /// base units of the quote currency with `quote_decimals` decimals.
+    ///
+    /// For example with a SOL/USD price of 150 (`15000000000 * 10^-8`), 2 SOL (`2_000_000_000`
+    /// lamports, 9 decimals) is worth `300_000_000` base units of a 6 decimals USD stablecoin.
+    ///
+    /// ```
+    /// use pyth_min::{math::Rounding, price_update::Price};
+    ///
+    /// let sol_usd = Price { price: 15000000000, conf: 0, exponent: -8, publish_time: 0 };
+    /// assert_eq!(sol_usd.value_of_amount(2_000_000_000, 9, 6, Rounding::Floor), Ok(300_000_000));
+    /// ```
+    ///
+    /// The intermediate product is computed in 128 bits and only rounded once. Fails with
+    /// `NegativePrice` if the price is negative and `MathOverflow` if the value does not fit a u64.
+    pub fn value_of_amount(
+        &self,
+        amount: u64,
+        mint_decimals: u8,
+        quote_decimals: u8,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        if self.price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+        // amount * 10^-mint_decimals * price * 10^exponent = value * 10^-quote_decimals
+        let exp = i64::from(self.exponent) - i64::from(mint_decimals) + i64::from(quote_decimals);
+        let value = mul_pow10_div(
+            i128::from(amount) * i128::from(self.price),
+            exp,
+            1,
+            rounding,
+        )?;
+        u64::try_from(value).map_err(|_| GetPriceError::MathOverflow)
+    }
+
+    /// The inverse of [`Price::value_of_amount`]: how many base units of a token with
+    /// `mint_decimals` decimals are worth `value` base units of the quote currency with
+    /// `quote_decimals` decimals.
+    ///
+    /// Fails with `ZeroPrice` or `NegativePrice` if the price cannot be divided by, and
+    /// `MathOverflow` if the amount does not fit a u64.
+    pub fn amount_for_value(
+        &self,
+        value: u64,
+        mint_decimals: u8,
+        quote_decimals: u8,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        if self.price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+        if self.price == 0 {
+            return Err(GetPriceError::ZeroPrice);
+        }
+        // amount = value * 10^-quote_decimals / (price * 10^exponent) * 10^mint_decimals
+        let exp = i64::from(mint_decimals) - i64::from(quote_decimals) - i64::from(self.exponent);
+        let amount = mul_pow10_div(value.into(), exp, self.price.into(), rounding)?;
+        u64::try_from(amount).map_err(|_| GetPriceError::MathOverflow)
+    }
+
+    /// The bottom of the confidence interval, `price - conf`, saturating at zero. The returned
+    /// price has a `conf` of zero.
+    pub fn lower_bound(&self) -> Price {
+        // Saturates at zero, so this cannot fail
+        self.conservative_price(Side::Collateral, BPS_DENOMINATOR)
+            .unwrap_or(Price {
+                price: 0,
+                conf: 0,
+                exponent: self.exponent,
+                publish_time: self.publish_time,
+            })
+    }
+
+    /// The top of the confidence interval, `price + conf`. The returned price has a `conf` of
+    /// zero. Fails with `NegativePrice` if even the top of the interval is negative.
+    pub fn upper_bound(&self) -> Result<Price> {
+        self.conservative_price(Side::Debt, BPS_DENOMINATOR)
+    }
+
+    /// The pessimistic price for `side`: `price - conf * k` for collateral (saturating at zero)
+    /// and `price + conf * k` for debt, where `k = conf_multiplier_bps / 10_000`. The scaled
+    /// confidence is rounded up. The returned price has a `conf` of zero.
+    pub fn conservative_price(&self, side: Side, conf_multiplier_bps: u64) -> Result<Price> {
+        let conf = div_round(
+            i128::from(self.conf) * i128::from(conf_multiplier_bps),
+            BPS_DENOMINATOR.into(),
+            Rounding::Ceil,
+        );
+        let price = match side {
+            Side::Collateral => (i128::from(self.price) - conf).max(0),
+            Side::Debt => i128::from(self.price) + conf,
+        };
+        if price < 0 {
+            return Err(GetPriceError::NegativePrice);
+        }
+
+        Ok(Price {
+            price: i64::try_from(price).map_err(|_| GetPriceError::MathOverflow)?,
+            conf: 0,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    /// [`Price::value_of_amount`] at the pessimistic price for `side` (see
+    /// [`Price::conservative_price`]), rounding down for collateral and up for debt.
+    pub fn conservative_value_of_amount(
+        &self,
+        amount: u64,
+        mint_decimals: u8,
+        quote_decimals: u8,
+        side: Side,
+        conf_multiplier_bps: u64,
+    ) -> Result<u64> {
+        let rounding = match side {
+            Side::Collateral => Rounding::Floor,
+            Side::Debt => Rounding::Ceil,
+        };
+        self.conservative_price(side, conf_multiplier_bps)?
+            .value_of_amount(amount, mint_decimals, quote_decimals, rounding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::price;
+
+    use super::*;
+
+    #[test]
+    fn value_of_amount() {
+        // SOL/USD from mainnet, 167.06469648
+        let sol_usd = price(16706469648, 0, -8, 0);
+
+        // 1.5 SOL in a 6 decimals stablecoin: 250.59704472
+        assert_eq!(
+            sol_usd.value_of_amount(1_500_000_000, 9, 6, Rounding::Floor),
+            Ok(250_597_044)
+        );
+        assert_eq!(
+            sol_usd.value_of_amount(1_500_000_000, 9, 6, Rounding::Ceil),
+            Ok(250_597_045)
+        );
+        // One lamport is worth less than a micro dollar
+        assert_eq!(sol_usd.value_of_amount(1, 9, 6, Rounding::Floor), Ok(0));
+        assert_eq!(sol_usd.value_of_amount(1, 9, 6, Rounding::Ceil), Ok(1));
+        // Quote with more decimals than the price
+        assert_eq!(
+            sol_usd.value_of_amount(1_000_000_000, 9, 10, Rounding::Floor),
+            Ok(1_670_646_964_800)
+        );
+    }
+
+    #[test]
+    fn value_of_amount_errors() {
+        assert_eq!(
+            price(-1, 0, -8, 0).value_of_amount(1, 9, 6, Rounding::Floor),
+            Err(GetPriceError::NegativePrice)
+        );
+        assert_eq!(
+            price(i64::MAX, 0, 0, 0).value_of_amount(u64::MAX, 0, 0, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+        assert_eq!(
+            price(1, 0, 0, 0).value_of_amount(1, 0, u8::MAX, Rounding::Floor),
+            Err(GetPriceError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn amount_for_value() {
+        let sol_usd = price(16706469648, 0, -8, 0);
+
+        // 250.597044 USD buys just under 1.5 SOL
+        assert_eq!(
+            sol_usd.amount_for_value(250_597_044, 9, 6, Rounding::Floor),
+            Ok(1_499_999_995)
+        );
+        assert_eq!(
+            sol_usd.amount_for_value(250_597_044, 9, 6, Rounding::Ceil),
+            Ok(1_499_999_996)
+        );
+        assert_eq!(
+            price(0, 0, -8, 0).amount_for_value(1, 9, 6, Rounding::Floor),
+            Err(GetPriceError::ZeroPrice)
+        );
+        assert_eq!(
+            price(-5, 0, -8, 0).amount_for_value(1, 9, 6, Rounding::Floor),
+            Err(GetPriceError::NegativePrice)
+        );
+    }
+
+    #[test]
+    fn value_and_amount_round_trip() {
+        let sol_usd = price(16706469648, 0, -8, 0);
+        for amount in [1u64, 999, 1_000_000_000, 123_456_789_012] {
+            let value = sol_usd
+                .value_of_amount(amount, 9, 6, Rounding::Ceil)
+                .unwrap();
+            let back = sol_usd
+                .amount_for_value(value, 9, 6, Rounding::Ceil)
+                .unwrap();
+            assert!(back >= amount, "{} -> {} -> {}", amount, value, back);
+        }
+    }
+
+    #[test]
+    fn confidence_bounds() {
+        let p = Price {
+            price: 1000,
+            conf: 30,
+            exponent: -2,
+            publish_time: 7,
+        };
+        assert_eq!(
+            p.lower_bound(),
+            Price {
+                price: 970,
+                conf: 0,
+                exponent: -2,
+                publish_time: 7
+            }
+        );
+        assert_eq!(p.upper_bound().unwrap().price, 1030);
+
+        let wide = Price { conf: 1500, ..p };
+        assert_eq!(wide.lower_bound().price, 0);
+        assert_eq!(wide.upper_bound().unwrap().price, 2500);
+
+        let negative = Price { price: -100, ..p };
+        assert_eq!(negative.upper_bound(), Err(GetPriceError::NegativePrice));
+
+        let huge = Price {
+            price: i64::MAX,
+            conf: u64::MAX,
+            ..p
+        };
+        assert_eq!(huge.lower_bound().price, 0);
+        assert_eq!(huge.upper_bound(), Err(GetPriceError::MathOverflow));
+    }
+
+    #[test]
+    fn conservative_price_multiplier() {
+        let p = price(1000, 15, -2, 0);
+
+        // 2.5x confidence, 37.5 rounded up to 38
+        assert_eq!(
+            p.conservative_price(Side::Collateral, 25_000)
+                .unwrap()
+                .price,
+            962
+        );
+        assert_eq!(
+            p.conservative_price(Side::Debt, 25_000).unwrap().price,
+            1038
+        );
+        // No confidence adjustment at all
+        assert_eq!(p.conservative_price(Side::Debt, 0).unwrap().price, 1000);
+    }
+
+    #[test]
+    fn conservative_value_of_amount() {
+        // SOL/USD 167.06469648 ± 0.22390601
+        let sol_usd = Price {
+            price: 16706469648,
+            conf: 22390601,
+            exponent: -8,
+            publish_time: 0,
+        };
+
+        let collateral = sol_usd
+            .conservative_value_of_amount(1_000_000_000, 9, 6, Side::Collateral, 10_000)
+            .unwrap();
+        let debt = sol_usd
+            .conservative_value_of_amount(1_000_000_000, 9, 6, Side::Debt, 10_000)
+            .unwrap();
+        assert_eq!(collateral, 166_840_790);
+        assert_eq!(debt, 167_288_603);
+    }
+}
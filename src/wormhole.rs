@@ -0,0 +1,519 @@
+//! Parsing and offline verification of Wormhole VAAs (Verified Action Approvals), the
+//! guardian-signed messages that carry the accumulator Merkle root from Pythnet to other chains.
+
+use crate::{
+    accumulator::{MerkleHash, UPDATE_TYPE_WORMHOLE_MERKLE},
+    byte_utils::Reader,
+    error::{ParseError, VerifyError},
+    keccak::keccak256,
+    price_update::VerificationLevel,
+    pythnet, secp256k1, ACCUMULATOR_EMITTER_ADDRESS,
+};
+
+/// The only VAA version in use
+pub const VAA_VERSION: u8 = 1;
+
+/// The first bytes of the accumulator root payload
+pub const ACCUMULATOR_ROOT_MAGIC: [u8; 4] = *b"AUWV";
+
+/// A guardian index followed by its signature
+const SIGNATURE_LEN: usize = 1 + 65;
+
+/// The Ethereum-style address of a guardian: the last 20 bytes of the Keccak-256 hash of its
+/// uncompressed public key.
+pub type GuardianAddress = [u8; 20];
+
+/// The signature of one guardian over the VAA body.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GuardianSignature<'a> {
+    /// Index of the guardian in the guardian set
+    pub guardian_index: u8,
+    /// Recoverable secp256k1 signature, `r || s || recovery id`
+    pub signature: &'a [u8; 65],
+}
+
+/// A borrowed view over a VAA.
+///
+/// Parsing does not check any signature, so nothing in a VAA can be trusted before its signatures
+/// are verified against the guardian set.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Vaa<'a> {
+    pub version: u8,
+    pub guardian_set_index: u32,
+    signatures: &'a [[u8; SIGNATURE_LEN]],
+    /// The signed part of the VAA, from `timestamp` to the end of the payload
+    pub body: &'a [u8],
+    pub timestamp: u32,
+    pub nonce: u32,
+    pub emitter_chain: u16,
+    pub emitter_address: &'a [u8; 32],
+    pub sequence: u64,
+    pub consistency_level: u8,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Vaa<'a> {
+    /// Fails with `UnsupportedVersion` for a version other than 1 and `Truncated` if `data` ends
+    /// before the payload. Everything after the consistency level is the payload.
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader::new(data);
+        let version = reader.u8()?;
+        if version != VAA_VERSION {
+            return Err(ParseError::UnsupportedVersion {
+                major: version,
+                minor: 0,
+            });
+        }
+        let guardian_set_index = reader.u32()?;
+        let num_signatures = reader.u8()?;
+        let signatures = reader.take(usize::from(num_signatures) * SIGNATURE_LEN)?;
+        // `take` returned whole signatures, so there is no remainder
+        let (signatures, _) = signatures.as_chunks();
+
+        let body = reader.rest();
+        let timestamp = reader.u32()?;
+        let nonce = reader.u32()?;
+        let emitter_chain = reader.u16()?;
+        let emitter_address = reader.array()?;
+        let sequence = reader.u64()?;
+        let consistency_level = reader.u8()?;
+
+        Ok(Vaa {
+            version,
+            guardian_set_index,
+            signatures,
+            body,
+            timestamp,
+            nonce,
+            emitter_chain,
+            emitter_address,
+            sequence,
+            consistency_level,
+            payload: reader.rest(),
+        })
+    }
+
+    pub fn signatures(&self) -> impl ExactSizeIterator<Item = GuardianSignature<'a>> + 'a {
+        self.signatures.iter().map(|signature| {
+            let [guardian_index, signature @ ..] = signature;
+            GuardianSignature {
+                guardian_index: *guardian_index,
+                signature,
+            }
+        })
+    }
+
+    /// The digest signed by the guardians: the double Keccak-256 hash of the body
+    pub fn digest(&self) -> [u8; 32] {
+        keccak256(&keccak256(self.body))
+    }
+
+    /// Whether the VAA was emitted by the Pythnet accumulator
+    pub fn is_from_accumulator(&self) -> bool {
+        self.emitter_chain == pythnet::CHAIN_ID
+            && *self.emitter_address == ACCUMULATOR_EMITTER_ADDRESS
+    }
+
+    /// Decode the payload as an accumulator Merkle root, after checking that the VAA was emitted
+    /// by the Pythnet accumulator. Fails with `InvalidEmitter` otherwise.
+    pub fn accumulator_root(&self) -> Result<WormholeMerkleRoot, VerifyError> {
+        if !self.is_from_accumulator() {
+            return Err(VerifyError::InvalidEmitter);
+        }
+        Ok(WormholeMerkleRoot::try_from_payload(self.payload)?)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Vaa<'a> {
+    type Error = ParseError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(data)
+    }
+}
+
+/// The guardians allowed to sign VAAs, as found in the Wormhole guardian set account of `index`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GuardianSet<'a> {
+    pub index: u32,
+    pub keys: &'a [GuardianAddress],
+}
+
+impl<'a> GuardianSet<'a> {
+    pub fn new(index: u32, keys: &'a [GuardianAddress]) -> Self {
+        GuardianSet { index, keys }
+    }
+
+    /// The number of signatures Wormhole requires: more than two thirds of the guardians
+    pub fn quorum(&self) -> usize {
+        self.keys.len() * 2 / 3 + 1
+    }
+}
+
+/// The guardian address of an uncompressed secp256k1 public key (`x || y`)
+pub fn guardian_address(public_key: &[u8; 64]) -> GuardianAddress {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(public_key)[12..]);
+    address
+}
+
+/// Check every guardian signature of `vaa` against `guardian_set` and return the verification
+/// level it reaches: `Full` with at least `quorum` signatures, `Partial` otherwise.
+///
+/// Fails with `QuorumTooLow` if `quorum` is below [`GuardianSet::quorum`], `GuardianSetMismatch`
+/// if the VAA names another guardian set, `UnsortedSignatures` unless guardian indices are
+/// strictly increasing, and `InvalidGuardianSignature` if any signature is not from the guardian
+/// at its index. A VAA carrying a bad signature is rejected outright rather than counted as
+/// partially verified.
+pub fn verify_vaa(
+    vaa: &Vaa,
+    guardian_set: &GuardianSet,
+    quorum: usize,
+) -> Result<VerificationLevel, VerifyError> {
+    if quorum < guardian_set.quorum() {
+        return Err(VerifyError::QuorumTooLow {
+            quorum,
+            minimum: guardian_set.quorum(),
+        });
+    }
+    if vaa.guardian_set_index != guardian_set.index {
+        return Err(VerifyError::GuardianSetMismatch {
+            expected: guardian_set.index,
+            actual: vaa.guardian_set_index,
+        });
+    }
+
+    let digest = vaa.digest();
+    let mut previous_index = None;
+    for GuardianSignature {
+        guardian_index,
+        signature,
+    } in vaa.signatures()
+    {
+        if previous_index.is_some_and(|previous| guardian_index <= previous) {
+            return Err(VerifyError::UnsortedSignatures);
+        }
+        previous_index = Some(guardian_index);
+
+        let expected = guardian_set
+            .keys
+            .get(usize::from(guardian_index))
+            .ok_or(VerifyError::InvalidGuardianSignature { guardian_index })?;
+        let signer = secp256k1::recover_public_key(&digest, signature)
+            .map(|public_key| guardian_address(&public_key));
+        if signer.as_ref() != Some(expected) {
+            return Err(VerifyError::InvalidGuardianSignature { guardian_index });
+        }
+    }
+
+    let num_signatures = vaa.signatures().len();
+    if num_signatures >= quorum {
+        Ok(VerificationLevel::Full)
+    } else {
+        Ok(VerificationLevel::Partial {
+            // A VAA holds at most u8::MAX signatures
+            num_signatures: num_signatures as u8,
+        })
+    }
+}
+
+/// The payload of accumulator VAAs: the root of the Merkle tree of every message produced in a
+/// Pythnet slot.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WormholeMerkleRoot {
+    /// The Pythnet slot of the messages
+    pub slot: u64,
+    /// How many slots of roots the accumulator keeps
+    pub ring_size: u32,
+    pub root: MerkleHash,
+}
+
+impl WormholeMerkleRoot {
+    /// Fails with `InvalidMagic` unless the payload starts with `AUWV`, `UnknownUpdateType` if it
+    /// is not a Merkle root, and `Truncated` or `TrailingBytes` if it has the wrong length.
+    pub fn try_from_payload(payload: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader::new(payload);
+        if *reader.array::<4>()? != ACCUMULATOR_ROOT_MAGIC {
+            return Err(ParseError::InvalidMagic);
+        }
+        let update_type = reader.u8()?;
+        if update_type != UPDATE_TYPE_WORMHOLE_MERKLE {
+            return Err(ParseError::UnknownUpdateType(update_type));
+        }
+        let root = WormholeMerkleRoot {
+            slot: reader.u64()?,
+            ring_size: reader.u32()?,
+            root: *reader.array()?,
+        };
+        reader.finish()?;
+
+        Ok(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_utils::hex_to_bytes;
+
+    use super::*;
+
+    fn root_payload(slot: u64, ring_size: u32, root: MerkleHash) -> Vec<u8> {
+        let mut payload = b"AUWV".to_vec();
+        payload.push(UPDATE_TYPE_WORMHOLE_MERKLE);
+        payload.extend_from_slice(&slot.to_be_bytes());
+        payload.extend_from_slice(&ring_size.to_be_bytes());
+        payload.extend_from_slice(&root);
+        payload
+    }
+
+    fn encode_vaa(emitter_chain: u16, emitter_address: &[u8; 32], payload: &[u8]) -> Vec<u8> {
+        let mut vaa = vec![VAA_VERSION];
+        vaa.extend_from_slice(&4u32.to_be_bytes());
+        vaa.push(2);
+        for guardian_index in [0u8, 3] {
+            vaa.push(guardian_index);
+            vaa.extend_from_slice(&[guardian_index + 1; 65]);
+        }
+        vaa.extend_from_slice(&1_717_782_833u32.to_be_bytes());
+        vaa.extend_from_slice(&7u32.to_be_bytes());
+        vaa.extend_from_slice(&emitter_chain.to_be_bytes());
+        vaa.extend_from_slice(emitter_address);
+        vaa.extend_from_slice(&123_456u64.to_be_bytes());
+        vaa.push(1);
+        vaa.extend_from_slice(payload);
+        vaa
+    }
+
+    fn guardian_key(guardian_index: u8) -> [u8; 32] {
+        keccak256(&[guardian_index])
+    }
+
+    fn guardian_keys(num_guardians: u8) -> Vec<GuardianAddress> {
+        (0..num_guardians)
+            .map(|i| guardian_address(&secp256k1::public_key(&guardian_key(i))))
+            .collect()
+    }
+
+    /// A VAA of guardian set 4 signed by the guardians at `signers`
+    fn signed_vaa(signers: &[u8], payload: &[u8]) -> Vec<u8> {
+        let unsigned = encode_vaa(pythnet::CHAIN_ID, &ACCUMULATOR_EMITTER_ADDRESS, payload);
+        let body = &unsigned[1 + 4 + 1 + 2 * 66..];
+        let digest = keccak256(&keccak256(body));
+
+        let mut vaa = vec![VAA_VERSION];
+        vaa.extend_from_slice(&4u32.to_be_bytes());
+        vaa.push(signers.len() as u8);
+        for guardian_index in signers {
+            vaa.push(*guardian_index);
+            vaa.extend_from_slice(&secp256k1::sign(&digest, &guardian_key(*guardian_index)));
+        }
+        vaa.extend_from_slice(body);
+        vaa
+    }
+
+    #[test]
+    fn parse_accumulator_vaa() {
+        let payload = root_payload(270_000_000, 10_000, [0xab; 20]);
+        let data = encode_vaa(pythnet::CHAIN_ID, &ACCUMULATOR_EMITTER_ADDRESS, &payload);
+
+        let vaa = Vaa::try_from_bytes(&data).unwrap();
+        assert_eq!((vaa.version, vaa.guardian_set_index), (1, 4));
+        assert!(vaa.signatures().eq([
+            GuardianSignature {
+                guardian_index: 0,
+                signature: &[1; 65]
+            },
+            GuardianSignature {
+                guardian_index: 3,
+                signature: &[4; 65]
+            },
+        ]));
+        assert_eq!(vaa.body, &data[1 + 4 + 1 + 2 * 66..]);
+        assert_eq!((vaa.timestamp, vaa.nonce), (1_717_782_833, 7));
+        assert_eq!(vaa.emitter_chain, 26);
+        assert_eq!(vaa.emitter_address, &ACCUMULATOR_EMITTER_ADDRESS);
+        assert_eq!((vaa.sequence, vaa.consistency_level), (123_456, 1));
+        assert_eq!(vaa.payload, &payload[..]);
+
+        assert_eq!(
+            vaa.accumulator_root(),
+            Ok(WormholeMerkleRoot {
+                slot: 270_000_000,
+                ring_size: 10_000,
+                root: [0xab; 20]
+            })
+        );
+    }
+
+    #[test]
+    fn reject_other_emitters() {
+        let payload = root_payload(1, 1, [0; 20]);
+        for data in [
+            encode_vaa(1, &ACCUMULATOR_EMITTER_ADDRESS, &payload),
+            encode_vaa(pythnet::CHAIN_ID, &[0; 32], &payload),
+        ] {
+            let vaa = Vaa::try_from(&data[..]).unwrap();
+            assert!(!vaa.is_from_accumulator());
+            assert_eq!(vaa.accumulator_root(), Err(VerifyError::InvalidEmitter));
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let payload = root_payload(1, 1, [0; 20]);
+        let data = encode_vaa(pythnet::CHAIN_ID, &ACCUMULATOR_EMITTER_ADDRESS, &payload);
+
+        let mut version_2 = data.clone();
+        version_2[0] = 2;
+        assert_eq!(
+            Vaa::try_from_bytes(&version_2),
+            Err(ParseError::UnsupportedVersion { major: 2, minor: 0 })
+        );
+        // Cut inside the body header
+        let header_len = 1 + 4 + 1 + 2 * 66 + 4 + 4 + 2 + 32 + 8 + 1;
+        assert_eq!(
+            Vaa::try_from_bytes(&data[..header_len - 1]),
+            Err(ParseError::Truncated {
+                expected: header_len,
+                actual: header_len - 1
+            })
+        );
+        // An empty payload is a valid VAA but not a valid root
+        let vaa = Vaa::try_from_bytes(&data[..header_len]).unwrap();
+        assert!(vaa.payload.is_empty());
+        assert_eq!(
+            vaa.accumulator_root(),
+            Err(VerifyError::Parse(ParseError::Truncated {
+                expected: 4,
+                actual: 0
+            }))
+        );
+
+        let mut bad_magic = payload.clone();
+        bad_magic[3] = b'X';
+        assert_eq!(
+            WormholeMerkleRoot::try_from_payload(&bad_magic),
+            Err(ParseError::InvalidMagic)
+        );
+        let mut bad_type = payload.clone();
+        bad_type[4] = 1;
+        assert_eq!(
+            WormholeMerkleRoot::try_from_payload(&bad_type),
+            Err(ParseError::UnknownUpdateType(1))
+        );
+        let mut trailing = payload.clone();
+        trailing.push(0);
+        assert_eq!(
+            WormholeMerkleRoot::try_from_payload(&trailing),
+            Err(ParseError::TrailingBytes {
+                expected: 37,
+                actual: 38
+            })
+        );
+    }
+
+    #[test]
+    fn guardian_addresses() {
+        let mut secret_key = [0u8; 32];
+        secret_key[31] = 1;
+        assert_eq!(
+            guardian_address(&secp256k1::public_key(&secret_key)).to_vec(),
+            hex_to_bytes("7e5f4552091a69125d5dfcb7b8c2659029395bdf")
+        );
+
+        let keys = [[0; 20]; 19];
+        assert_eq!(GuardianSet::new(4, &keys).quorum(), 13);
+        assert_eq!(GuardianSet::new(4, &keys[..1]).quorum(), 1);
+    }
+
+    #[test]
+    fn verify_guardian_signatures() {
+        let keys = guardian_keys(7);
+        let guardian_set = GuardianSet::new(4, &keys);
+        assert_eq!(guardian_set.quorum(), 5);
+        let payload = root_payload(1, 1, [0; 20]);
+
+        let data = signed_vaa(&[0, 2, 3, 5, 6], &payload);
+        let vaa = Vaa::try_from_bytes(&data).unwrap();
+        assert_eq!(vaa.digest(), keccak256(&keccak256(vaa.body)));
+        assert_eq!(
+            verify_vaa(&vaa, &guardian_set, guardian_set.quorum()),
+            Ok(VerificationLevel::Full)
+        );
+        assert_eq!(
+            verify_vaa(&vaa, &guardian_set, 6),
+            Ok(VerificationLevel::Partial { num_signatures: 5 })
+        );
+
+        let data = signed_vaa(&[1, 4], &payload);
+        let vaa = Vaa::try_from_bytes(&data).unwrap();
+        assert_eq!(
+            verify_vaa(&vaa, &guardian_set, guardian_set.quorum()),
+            Ok(VerificationLevel::Partial { num_signatures: 2 })
+        );
+        for quorum in [0, 2, 4] {
+            assert_eq!(
+                verify_vaa(&vaa, &guardian_set, quorum),
+                Err(VerifyError::QuorumTooLow { quorum, minimum: 5 })
+            );
+        }
+
+        let data = signed_vaa(&[], &payload);
+        let vaa = Vaa::try_from_bytes(&data).unwrap();
+        assert_eq!(
+            verify_vaa(&vaa, &guardian_set, guardian_set.quorum()),
+            Ok(VerificationLevel::Partial { num_signatures: 0 })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_signatures() {
+        let keys = guardian_keys(3);
+        let guardian_set = GuardianSet::new(4, &keys);
+        let payload = root_payload(1, 1, [0; 20]);
+        let verify = |data: &[u8]| {
+            let vaa = Vaa::try_from_bytes(data).unwrap();
+            verify_vaa(&vaa, &guardian_set, guardian_set.quorum())
+        };
+
+        let data = signed_vaa(&[0, 2], &payload);
+        assert_eq!(
+            verify_vaa(
+                &Vaa::try_from_bytes(&data).unwrap(),
+                &GuardianSet::new(3, &keys),
+                guardian_set.quorum()
+            ),
+            Err(VerifyError::GuardianSetMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+
+        // Changing the signed body invalidates every signature
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            verify(&tampered),
+            Err(VerifyError::InvalidGuardianSignature { guardian_index: 0 })
+        );
+
+        // A signature attributed to another guardian
+        let mut swapped = data.clone();
+        swapped[1 + 4 + 1 + 66] = 1;
+        assert_eq!(
+            verify(&swapped),
+            Err(VerifyError::InvalidGuardianSignature { guardian_index: 1 })
+        );
+
+        assert_eq!(
+            verify(&signed_vaa(&[0, 3], &payload)),
+            Err(VerifyError::InvalidGuardianSignature { guardian_index: 3 })
+        );
+        for signers in [&[1, 0][..], &[1, 1]] {
+            assert_eq!(
+                verify(&signed_vaa(signers, &payload)),
+                Err(VerifyError::UnsortedSignatures)
+            );
+        }
+    }
+}